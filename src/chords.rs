@@ -0,0 +1,459 @@
+//! Chord diagrams: `n` points on the unit circle, each joined to the point a rule picks for it.
+//!
+//! The rule is an expression in the index `i`, taken mod `n`. Times tables `i -> m·i` trace out a cardioid
+//! for `m = 2` and a nephroid for `m = 3`; `i -> i + k` gives back the star polygon `{n/k}`.
+
+use crate::{
+    geometry::{parse_number, ParseErrorKind, Point},
+    symbol::{SymbolError, SymbolErrorKind}
+};
+use std::{
+    f32::consts::TAU,
+    fmt,
+    ops::Range,
+    str::FromStr
+};
+
+/// Most points a diagram can have.
+pub const MAX_POINTS: usize = 10_000;
+
+/// An integer expression in `i` and `n`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Expr {
+    Index,
+    Count,
+    Number(u64),
+    Neg(Box<Expr>),
+    Add(Box<Expr>, Box<Expr>),
+    Sub(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
+    Pow(Box<Expr>, Box<Expr>)
+}
+impl Expr {
+    /// The value for point `i` of `n`, or an error if it doesn't fit in 128 bits or has a negative exponent.
+    pub fn eval(&self, i: i128, n: i128) -> Result<i128, ChordError> {
+        let binary = |a: &Expr, b: &Expr, op: fn(i128, i128) -> Option<i128>| {
+            op(a.eval(i, n)?, b.eval(i, n)?).ok_or(ChordError::Overflow)
+        };
+        match self {
+            Expr::Index => Ok(i),
+            Expr::Count => Ok(n),
+            Expr::Number(x) => Ok(*x as i128),
+            Expr::Neg(a) => a.eval(i, n)?.checked_neg().ok_or(ChordError::Overflow),
+            Expr::Add(a, b) => binary(a, b, i128::checked_add),
+            Expr::Sub(a, b) => binary(a, b, i128::checked_sub),
+            Expr::Mul(a, b) => binary(a, b, i128::checked_mul),
+            Expr::Pow(a, b) => {
+                let exponent = b.eval(i, n)?;
+                if exponent < 0 {
+                    return Err(ChordError::NegativeExponent)
+                }
+                let exponent = u32::try_from(exponent).map_err(|_| ChordError::Overflow)?;
+                a.eval(i, n)?.checked_pow(exponent).ok_or(ChordError::Overflow)
+            }
+        }
+    }
+
+    /// The value with real `i` and `n`, for animating.
+    pub fn eval_real(&self, i: f32, n: f32) -> f32 {
+        match self {
+            Expr::Index => i,
+            Expr::Count => n,
+            Expr::Number(x) => *x as f32,
+            Expr::Neg(a) => -a.eval_real(i, n),
+            Expr::Add(a, b) => a.eval_real(i, n) + b.eval_real(i, n),
+            Expr::Sub(a, b) => a.eval_real(i, n) - b.eval_real(i, n),
+            Expr::Mul(a, b) => a.eval_real(i, n)*b.eval_real(i, n),
+            Expr::Pow(a, b) => a.eval_real(i, n).powf(b.eval_real(i, n))
+        }
+    }
+
+    /// How tightly the expression binds, for deciding where [`fmt::Display`] needs parentheses.
+    fn precedence(&self) -> u8 {
+        match self {
+            Expr::Add(..) | Expr::Sub(..) => 1,
+            Expr::Mul(..) => 2,
+            Expr::Neg(..) => 3,
+            Expr::Pow(..) => 4,
+            Expr::Index | Expr::Count | Expr::Number(_) => 5
+        }
+    }
+}
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let operand = |f: &mut fmt::Formatter, e: &Expr, min: u8| {
+            if e.precedence() < min { write!(f, "({})", e) } else { write!(f, "{}", e) }
+        };
+        let binary = |f: &mut fmt::Formatter, a: &Expr, op: char, b: &Expr, (left, right): (u8, u8)| {
+            operand(f, a, left)?;
+            write!(f, "{}", op)?;
+            operand(f, b, right)
+        };
+        // Subtraction and powers only group one way, so the other side needs parentheses at equal precedence.
+        match self {
+            Expr::Index => write!(f, "i"),
+            Expr::Count => write!(f, "n"),
+            Expr::Number(x) => write!(f, "{}", x),
+            Expr::Neg(a) => {
+                write!(f, "-")?;
+                operand(f, a, 3)
+            },
+            Expr::Add(a, b) => binary(f, a, '+', b, (1, 1)),
+            Expr::Sub(a, b) => binary(f, a, '-', b, (1, 2)),
+            Expr::Mul(a, b) => binary(f, a, '*', b, (2, 2)),
+            Expr::Pow(a, b) => binary(f, a, '^', b, (5, 3))
+        }
+    }
+}
+
+/// Recursive descent over `+ -`, then `*`, then unary `-`, then `^`, which groups to the right.
+struct Parser<'a> {
+    s: &'a str,
+    /// Byte offset of `s` in the whole input, for error spans.
+    offset: usize,
+    position: usize
+}
+impl<'a> Parser<'a> {
+    /// Skips spaces and returns the next character without consuming it.
+    fn peek(&mut self) -> Option<char> {
+        let rest = &self.s[self.position..];
+        self.position += rest.len() - rest.trim_start().len();
+        self.s[self.position..].chars().next()
+    }
+
+    fn error(&self, span: Range<usize>, kind: SymbolErrorKind) -> SymbolError {
+        SymbolError::new(self.offset + span.start..self.offset + span.end, kind)
+    }
+
+    fn sum(&mut self) -> Result<Expr, SymbolError> {
+        let mut e = self.product()?;
+        while let Some(op @ ('+' | '-')) = self.peek() {
+            self.position += 1;
+            let b = Box::new(self.product()?);
+            e = if op == '+' { Expr::Add(Box::new(e), b) } else { Expr::Sub(Box::new(e), b) };
+        }
+        Ok(e)
+    }
+
+    fn product(&mut self) -> Result<Expr, SymbolError> {
+        let mut e = self.negation()?;
+        while self.peek() == Some('*') {
+            self.position += 1;
+            e = Expr::Mul(Box::new(e), Box::new(self.negation()?));
+        }
+        Ok(e)
+    }
+
+    fn negation(&mut self) -> Result<Expr, SymbolError> {
+        if self.peek() == Some('-') {
+            self.position += 1;
+            return Ok(Expr::Neg(Box::new(self.negation()?)))
+        }
+        self.power()
+    }
+
+    fn power(&mut self) -> Result<Expr, SymbolError> {
+        let base = self.atom()?;
+        if self.peek() == Some('^') {
+            self.position += 1;
+            return Ok(Expr::Pow(Box::new(base), Box::new(self.negation()?)))
+        }
+        Ok(base)
+    }
+
+    fn atom(&mut self) -> Result<Expr, SymbolError> {
+        let next = self.peek();
+        let start = self.position;
+        match next {
+            Some('i') => {
+                self.position += 1;
+                Ok(Expr::Index)
+            },
+            Some('n') => {
+                self.position += 1;
+                Ok(Expr::Count)
+            },
+            Some('(') => {
+                let open = self.position;
+                self.position += 1;
+                let e = self.sum()?;
+                if self.peek() != Some(')') {
+                    return Err(self.error(open..open + 1, SymbolErrorKind::UnclosedParenthesis))
+                }
+                self.position += 1;
+                Ok(e)
+            },
+            Some(ch) if ch.is_ascii_digit() => {
+                let len = self.s[self.position..].find(|ch: char| !ch.is_ascii_digit()).unwrap_or(self.s.len() - self.position);
+                let digits = &self.s[self.position..self.position + len];
+                let x = parse_number(digits, self.offset + self.position)?;
+                self.position += len;
+                Ok(Expr::Number(x as u64))
+            },
+            Some(ch) => Err(self.error(start..start + ch.len_utf8(), SymbolErrorKind::ExpectedOperand)),
+            None => Err(self.error(start..start, SymbolErrorKind::ExpectedOperand))
+        }
+    }
+}
+
+/// A chord diagram `n:rule`, joining point `i` to point `rule(i) mod n`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChordRule {
+    n: usize,
+    rule: Expr,
+    /// Where each point's chord ends.
+    ends: Vec<usize>
+}
+impl ChordRule {
+    pub fn new(n: usize, rule: Expr) -> Result<Self, ChordError> {
+        if n < 2 {
+            return Err(ChordError::TooFewPoints)
+        }
+        if n > MAX_POINTS {
+            return Err(ChordError::TooManyPoints)
+        }
+        let ends = (0..n).map(|i| Ok(rule.eval(i as i128, n as i128)?.rem_euclid(n as i128) as usize)).collect::<Result<_, _>>()?;
+        Ok(ChordRule {
+            n,
+            rule,
+            ends
+        })
+    }
+
+    /// The times table `i -> m·i`.
+    pub fn times(n: usize, m: u64) -> Result<Self, ChordError> {
+        ChordRule::new(n, Expr::Mul(Box::new(Expr::Index), Box::new(Expr::Number(m))))
+    }
+
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    pub fn rule(&self) -> &Expr {
+        &self.rule
+    }
+
+    /// `m` if this is the times table `i -> m·i`.
+    pub fn multiplier(&self) -> Option<u64> {
+        match &self.rule {
+            Expr::Mul(a, b) => match (&**a, &**b) {
+                (Expr::Index, Expr::Number(m)) | (Expr::Number(m), Expr::Index) => Some(*m),
+                _ => None
+            },
+            _ => None
+        }
+    }
+
+    /// The points, placed like the vertices of a polygon.
+    pub fn vertices(&self) -> Vec<Point> {
+        vertices_at(self.n as f32)
+    }
+
+    /// Pairs of indices into [`ChordRule::vertices`], leaving out points that map to themselves.
+    pub fn edges(&self) -> Vec<[usize; 2]> {
+        self.ends.iter().enumerate().filter(|&(i, &j)| i != j).map(|(i, &j)| [i, j]).collect()
+    }
+
+    /// Points that map to themselves and so have no chord.
+    pub fn fixed_points(&self) -> usize {
+        self.ends.iter().enumerate().filter(|&(i, &j)| i == j).count()
+    }
+
+    /// The chords for a real `n`, and a real multiplier `m` in place of the rule if given. Whole values give
+    /// [`ChordRule::edges`]; in between, the chords end between the points.
+    pub fn edges_at(&self, n: f32, m: Option<f32>) -> Vec<[Point; 2]> {
+        (0..n.ceil() as usize).map(|i| {
+            let i = i as f32;
+            let end = m.map_or_else(|| self.rule.eval_real(i, n), |m| m*i);
+            [point(i, n), point(end, n)]
+        }).collect()
+    }
+}
+
+fn point(i: f32, n: f32) -> Point {
+    let (sin, cos) = (TAU*i/n).sin_cos();
+    Point::new(cos, sin)
+}
+
+/// The points of a diagram with a real `n`, the last one appearing at `(1, 0)` as `n` passes each whole number
+/// and sliding round as it grows.
+pub fn vertices_at(n: f32) -> Vec<Point> {
+    (0..n.ceil() as usize).map(|i| point(i as f32, n)).collect()
+}
+
+impl fmt::Display for ChordRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.multiplier() {
+            Some(m) => write!(f, "{}:{}", self.n, m),
+            None => write!(f, "{}:{}", self.n, self.rule)
+        }
+    }
+}
+
+/// Accepts `n:rule`, where the rule uses `i`, `n`, whole numbers, `+ - * ^` and parentheses.
+/// A bare number `m` is short for the times table `i*m`.
+impl FromStr for ChordRule {
+    type Err = SymbolError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let colon = match s.find(':') {
+            Some(colon) => colon,
+            None => return Err(SymbolError::new(s.len()..s.len(), SymbolErrorKind::MissingColon))
+        };
+        let n = parse_number(&s[..colon], 0)?;
+        let mut parser = Parser {
+            s: &s[colon + 1..],
+            offset: colon + 1,
+            position: 0
+        };
+        let rule = parser.sum()?;
+        if let Some(ch) = parser.peek() {
+            let start = parser.offset + parser.position;
+            return Err(SymbolError::new(start..start + ch.len_utf8(), ParseErrorKind::InvalidCharacter(ch).into()))
+        }
+        let rule = match rule {
+            Expr::Number(m) => Expr::Mul(Box::new(Expr::Index), Box::new(Expr::Number(m))),
+            rule => rule
+        };
+        ChordRule::new(n, rule).map_err(|e| {
+            let span = match e {
+                ChordError::TooFewPoints | ChordError::TooManyPoints => 0..colon,
+                _ => colon + 1..s.len()
+            };
+            SymbolError::new(span, e.into())
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChordError {
+    TooFewPoints,
+    TooManyPoints,
+    Overflow,
+    NegativeExponent
+}
+impl fmt::Display for ChordError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ChordError::TooFewPoints => write!(f, "a chord diagram needs at least 2 points"),
+            ChordError::TooManyPoints => write!(f, "a chord diagram can have at most {} points", MAX_POINTS),
+            ChordError::Overflow => write!(f, "the rule gets too large for some i"),
+            ChordError::NegativeExponent => write!(f, "exponents can't be negative")
+        }
+    }
+}
+impl std::error::Error for ChordError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(s: &str) -> (Range<usize>, SymbolErrorKind) {
+        let e = s.parse::<ChordRule>().unwrap_err();
+        (e.span, e.kind)
+    }
+
+    fn expr(s: &str) -> Expr {
+        Parser {
+            s,
+            offset: 0,
+            position: 0
+        }.sum().unwrap()
+    }
+
+    fn value(s: &str) -> i128 {
+        expr(s).eval(3, 10).unwrap()
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(value("1 + 2*3"), 7);
+        assert_eq!(value("(1 + 2)*3"), 9);
+        assert_eq!(value("2*3^2"), 18);
+        assert_eq!(value("10 - 3 - 2"), 5);
+        assert_eq!(value("n - i*2"), 4);
+    }
+
+    #[test]
+    fn powers_group_right() {
+        assert_eq!(value("2^3^2"), 512);
+        assert_eq!(value("(2^3)^2"), 64);
+        assert_eq!(value("i^2^0"), 3);
+    }
+
+    #[test]
+    fn unary_minus() {
+        assert_eq!(value("-2^2"), -4);
+        assert_eq!(value("(-2)^2"), 4);
+        assert_eq!(value("--i"), 3);
+        assert_eq!(value("2*-i"), -6);
+        assert_eq!(value("2^-0"), 1);
+    }
+
+    #[test]
+    fn eval() {
+        assert_eq!(expr("i^-1").eval(3, 10), Err(ChordError::NegativeExponent));
+        assert_eq!(expr("2^200").eval(0, 10), Err(ChordError::Overflow));
+        assert_eq!(expr("i^99999999999").eval(1, 10), Err(ChordError::Overflow));
+        assert_eq!(expr("-i").eval(i128::MIN, 10), Err(ChordError::Overflow));
+        assert_eq!(expr("i*i + 1").eval_real(0.5, 10.0), 1.25);
+    }
+
+    #[test]
+    fn ends_wrap() {
+        let rule = "10:-i - 1".parse::<ChordRule>().unwrap();
+        assert_eq!(rule.edges(), [[0, 9], [1, 8], [2, 7], [3, 6], [4, 5], [5, 4], [6, 3], [7, 2], [8, 1], [9, 0]]);
+        assert_eq!(rule.fixed_points(), 0);
+        assert_eq!("10:i^2".parse::<ChordRule>().unwrap().fixed_points(), 4);
+    }
+
+    #[test]
+    fn times_table() {
+        let rule = "200:2".parse::<ChordRule>().unwrap();
+        assert_eq!(rule, ChordRule::times(200, 2).unwrap());
+        assert_eq!(rule.multiplier(), Some(2));
+        assert_eq!(rule.to_string(), "200:2");
+        assert_eq!("200:(3)".parse::<ChordRule>().unwrap().multiplier(), Some(3));
+        assert_eq!("200:2*i".parse::<ChordRule>().unwrap().multiplier(), Some(2));
+        assert_eq!("200:i + 2".parse::<ChordRule>().unwrap().multiplier(), None);
+    }
+
+    #[test]
+    fn round_trip() {
+        for s in ["10:i^2", "10:i-(n-1)", "10:(i-1)*(i+1)", "10:(2^i)^2", "10:2^i^2", "10:-i^2", "10:(-i)^2", "10:i^(n-i)+1", "10:i^--2", "10:--i", "10:n*i-i"] {
+            let rule = s.parse::<ChordRule>().unwrap();
+            assert_eq!(rule.to_string(), s);
+            assert_eq!(rule.to_string().parse::<ChordRule>().unwrap(), rule);
+        }
+        assert_eq!("10: i ^ 2 ".parse::<ChordRule>().unwrap().to_string(), "10:i^2");
+        assert_eq!("10:((i))".parse::<ChordRule>().unwrap().to_string(), "10:i");
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(error("5"), (1..1, SymbolErrorKind::MissingColon));
+        assert_eq!(error("x:2"), (0..1, SymbolErrorKind::Polygon(ParseErrorKind::InvalidCharacter('x'))));
+        assert_eq!(error("1:2"), (0..1, SymbolErrorKind::Chords(ChordError::TooFewPoints)));
+        assert_eq!(error("5:"), (2..2, SymbolErrorKind::ExpectedOperand));
+        assert_eq!(error("5:i +"), (5..5, SymbolErrorKind::ExpectedOperand));
+        assert_eq!(error("5:x"), (2..3, SymbolErrorKind::ExpectedOperand));
+        assert_eq!(error("5:i x"), (4..5, SymbolErrorKind::Polygon(ParseErrorKind::InvalidCharacter('x'))));
+        assert_eq!(error("5:i^-1"), (2..6, SymbolErrorKind::Chords(ChordError::NegativeExponent)));
+        assert_eq!(error("5:2^200"), (2..7, SymbolErrorKind::Chords(ChordError::Overflow)));
+    }
+
+    #[test]
+    fn parentheses() {
+        assert_eq!(error("5:(i + 1"), (2..3, SymbolErrorKind::UnclosedParenthesis));
+        assert_eq!(error("5:2*((i)"), (4..5, SymbolErrorKind::UnclosedParenthesis));
+        assert_eq!(error("5:i)"), (3..4, SymbolErrorKind::Polygon(ParseErrorKind::InvalidCharacter(')'))));
+        assert_eq!(error("5:()"), (3..4, SymbolErrorKind::ExpectedOperand));
+    }
+
+    #[test]
+    fn too_many_points() {
+        assert_eq!(error("100000000:2"), (0..9, SymbolErrorKind::Chords(ChordError::TooManyPoints)));
+        assert_eq!(ChordRule::times(MAX_POINTS + 1, 2), Err(ChordError::TooManyPoints));
+        assert_eq!(ChordRule::times(MAX_POINTS, 2).unwrap().edges().len(), MAX_POINTS - 1);
+    }
+}
@@ -0,0 +1,359 @@
+use bevy::render::color::Color;
+use shaper_2d::{
+    fill::FillMode,
+    geometry::{Polygon, PolygonError},
+    palette::{component_colors, hex, rgba8, PALETTE},
+    raster::{self, RasterOptions},
+    stroke::Join,
+    svg::{self, SvgOptions},
+    symbol::Symbol,
+    variant::Variant
+};
+use std::{
+    env,
+    path::{Path, PathBuf}
+};
+
+/// Largest `--size`, a 1 GiB image.
+const MAX_SIZE: u32 = 16384;
+
+const USAGE: &str = "\
+usage: shaper_2d render <n/k | p,q | n:rule | ops{n/k} | hypo(R, r, d) | epi(R, r, d) | rose(n/d)> [options] [-o <file>]
+       shaper_2d render --all-k <n> [options] [-o <dir>]
+
+options: --size <px>  --format svg|png  --fill evenodd|nonzero|density
+         --intersections  --outline  --isotoxal <depth>  --isogonal <cut>  --overlay <n/k>
+         --background <color>  --color <color>  --vertex-color <color>
+         --stroke-width <px>  --join miter|round|bevel  --vertex-radius <px>
+
+--all-k writes one file per distinct star polygon {n/1} .. {n/(n/2)}.
+--isotoxal and --isogonal draw a variant of the star polygon instead, with a depth or cut from 0 to 1.
+The operators t, r, d and s truncate, rectify, dualise and alternate what follows, as in tr{8/3}.
+--overlay draws a star polygon over a hypotrochoid, epitrochoid or rose.
+Colours are #rrggbb or one of black, white, grey, red, orange, yellow, green, cyan, blue and navy.
+SVGs default to black on a transparent background and PNGs to white on black. --vertex-radius 0 hides
+the vertex dots.
+Tilings {p,q}, chord diagrams n:rule, derived polygons, variants and curves are drawn without fills,
+intersections or outlines.";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Format {
+    Svg,
+    Png
+}
+impl Format {
+    fn extension(self) -> &'static str {
+        match self {
+            Format::Svg => "svg",
+            Format::Png => "png"
+        }
+    }
+
+    fn from_path(path: &Path) -> Option<Format> {
+        match path.extension()?.to_str()? {
+            "svg" => Some(Format::Svg),
+            "png" => Some(Format::Png),
+            _ => None
+        }
+    }
+}
+
+struct Render {
+    symbols: Vec<Symbol>,
+    all_k: bool,
+    size: u32,
+    fill: Option<FillMode>,
+    intersections: bool,
+    outline: bool,
+    variant: Option<Variant>,
+    overlay: Option<Polygon>,
+    style: Style,
+    format: Format,
+    output: Option<PathBuf>
+}
+
+/// What `--background` and the other style options override, leaving the format's own defaults otherwise.
+#[derive(Default)]
+struct Style {
+    background: Option<Color>,
+    color: Option<Color>,
+    vertex_color: Option<Color>,
+    stroke_width: Option<f32>,
+    join: Option<Join>,
+    vertex_radius: Option<f32>
+}
+
+fn parse_color(arg: &str, value: &str) -> Result<Color, String> {
+    match PALETTE.iter().find(|(name, _)| *name == value) {
+        Some(&(_, color)) => Ok(color),
+        None => value.strip_prefix('#').filter(|hex| hex.len() == 6).and_then(|hex| Color::hex(hex).ok())
+            .ok_or(format!("{} needs #rrggbb or a colour name, not '{}'", arg, value))
+    }
+}
+
+/// Pixels, or SVG user units.
+fn parse_length(arg: &str, value: &str, min: f32) -> Result<f32, String> {
+    value.parse::<f32>().ok().filter(|v| (min..=100.0).contains(v))
+        .ok_or(format!("{} needs a value from {} to 100", arg, min))
+}
+
+/// The render to do, or `None` if `--help` was asked for.
+fn parse(mut args: impl Iterator<Item = String>) -> Result<Option<Render>, String> {
+    let mut symbol = None;
+    let mut all_k = None;
+    let mut size = 500;
+    let mut fill = None;
+    let mut intersections = false;
+    let mut outline = false;
+    let mut variant = None;
+    let mut overlay = None;
+    let mut style = Style::default();
+    let mut format = None;
+    let mut output = None;
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--size" => size = value()?.parse().map_err(|_| "--size must be a positive integer".to_owned())?,
+            "--format" => format = Some(match value()?.as_str() {
+                "svg" => Format::Svg,
+                "png" => Format::Png,
+                f => return Err(format!("unknown format '{}'", f))
+            }),
+            "--fill" => fill = Some(match value()?.as_str() {
+                "evenodd" => FillMode::EvenOdd,
+                "nonzero" => FillMode::NonZero,
+                "density" => FillMode::Density,
+                f => return Err(format!("unknown fill mode '{}'", f))
+            }),
+            "--intersections" => intersections = true,
+            "--outline" => outline = true,
+            "--isotoxal" | "--isogonal" => {
+                let value = value()?.parse::<f32>().ok().filter(|v| (0.0..=1.0).contains(v))
+                    .ok_or(format!("{} needs a value from 0 to 1", arg))?;
+                variant = Some(if arg == "--isotoxal" { Variant::Isotoxal(value) } else { Variant::Isogonal(value) });
+            },
+            "--overlay" => {
+                let value = value()?;
+                overlay = Some(value.parse::<Polygon>().map_err(|e| format!("{}: {}", value, e))?);
+            },
+            "--background" => style.background = Some(parse_color(&arg, &value()?)?),
+            "--color" => style.color = Some(parse_color(&arg, &value()?)?),
+            "--vertex-color" => style.vertex_color = Some(parse_color(&arg, &value()?)?),
+            "--stroke-width" => style.stroke_width = Some(parse_length(&arg, &value()?, 0.1)?),
+            "--join" => style.join = Some(match value()?.as_str() {
+                "miter" => Join::Miter,
+                "round" => Join::Round,
+                "bevel" => Join::Bevel,
+                j => return Err(format!("unknown join '{}'", j))
+            }),
+            "--vertex-radius" => style.vertex_radius = Some(parse_length(&arg, &value()?, 0.0)?),
+            "-o" | "--output" => output = Some(PathBuf::from(value()?)),
+            "--all-k" => all_k = Some(value()?.parse::<usize>().map_err(|_| "--all-k needs a vertex count".to_owned())?),
+            "-h" | "--help" => return Ok(None),
+            _ if symbol.is_none() && !arg.starts_with('-') => symbol = Some(arg),
+            _ => return Err(format!("unexpected argument '{}'\n\n{}", arg, USAGE))
+        }
+    }
+    if size == 0 {
+        return Err("--size must be a positive integer".to_owned())
+    }
+    if size > MAX_SIZE {
+        return Err(format!("--size can be at most {}\n\n{}", MAX_SIZE, USAGE))
+    }
+    let symbols = match (symbol, all_k) {
+        (Some(s), None) => vec![s.parse::<Symbol>().map_err(|e| format!("{}: {}", s, e))?],
+        (None, Some(n)) if n < 3 => return Err(format!("{{{}}}: {}", n, PolygonError::TooFewVertices)),
+        (None, Some(n)) => (1..=n/2).map(|k| Polygon::new(n, k).map(Symbol::Polygon).map_err(|e| format!("{{{}}}: {}", n, e))).collect::<Result<_, _>>()?,
+        (Some(_), Some(_)) => return Err("give either a symbol or --all-k, not both".to_owned()),
+        (None, None) => return Err(USAGE.to_owned())
+    };
+    if variant.is_some() && symbols.iter().any(|symbol| !matches!(symbol, Symbol::Polygon(_))) {
+        return Err("--isotoxal and --isogonal only apply to star polygons".to_owned())
+    }
+    if overlay.is_some() && symbols.iter().any(|symbol| !matches!(symbol, Symbol::Curve(_))) {
+        return Err("--overlay only applies to curves".to_owned())
+    }
+    // A single file's extension names its format, and has to agree with --format if both are given.
+    let extension = output.as_deref().filter(|_| all_k.is_none()).and_then(Format::from_path);
+    let format = match (format, extension) {
+        (Some(format), Some(extension)) if format != extension => {
+            return Err(format!("--format {} doesn't match the .{} output file", format.extension(), extension.extension()))
+        },
+        (format, extension) => format.or(extension).unwrap_or(Format::Png)
+    };
+    Ok(Some(Render {
+        symbols,
+        all_k: all_k.is_some(),
+        size,
+        fill,
+        intersections,
+        outline,
+        variant,
+        overlay,
+        style,
+        format,
+        output
+    }))
+}
+
+fn write(symbol: &Symbol, path: &Path, render: &Render) -> Result<(), String> {
+    let size = render.size;
+    let scale = size as f32*0.45;
+    let style = &render.style;
+    let vertex_radius = |default| match style.vertex_radius {
+        Some(radius) => (radius > 0.0).then_some(radius),
+        None => default
+    };
+    let colors = match symbol {
+        Symbol::Polygon(polygon) => component_colors(polygon.components()),
+        _ => Vec::new()
+    };
+    let svg_options = || {
+        let defaults = SvgOptions::default();
+        SvgOptions {
+            scale,
+            margin: size as f32/2.0 - scale,
+            stroke_width: style.stroke_width.unwrap_or(defaults.stroke_width),
+            join: style.join.unwrap_or(defaults.join),
+            color: style.color.map(hex).unwrap_or(defaults.color),
+            background: style.background.map(hex).or(defaults.background),
+            vertex_radius: vertex_radius(defaults.vertex_radius),
+            vertex_color: style.vertex_color.map(hex).unwrap_or(defaults.vertex_color),
+            fill: render.fill,
+            intersection_radius: render.intersections.then_some(2.0),
+            outline: render.outline,
+            component_colors: colors.iter().copied().map(hex).collect(),
+            ..defaults
+        }
+    };
+    let raster_options = || {
+        let defaults = RasterOptions::default();
+        RasterOptions {
+            width: size,
+            height: size,
+            scale,
+            background: style.background.map(rgba8).unwrap_or(defaults.background),
+            color: style.color.map(rgba8).unwrap_or(defaults.color),
+            line_width: style.stroke_width.unwrap_or(defaults.line_width),
+            join: style.join.unwrap_or(defaults.join),
+            vertex_radius: vertex_radius(defaults.vertex_radius),
+            vertex_color: style.vertex_color.map(rgba8).unwrap_or(defaults.vertex_color),
+            fill: render.fill,
+            intersection_radius: render.intersections.then_some(2.0),
+            outline: render.outline,
+            component_colors: colors.iter().copied().map(rgba8).collect(),
+            ..defaults
+        }
+    };
+    let result = match (symbol, symbol.lines(render.variant, render.overlay.as_ref()), render.format) {
+        (Symbol::Polygon(polygon), None, Format::Svg) => svg::write_svg(path, polygon, &svg_options()),
+        (Symbol::Polygon(polygon), None, Format::Png) => raster::write_png(path, polygon, &raster_options()),
+        (_, lines, Format::Svg) => {
+            let (vertices, lines, closed) = lines.unwrap_or_default();
+            svg::write_lines_svg(path, &vertices, &lines, closed, &svg_options())
+        },
+        (_, lines, Format::Png) => {
+            let (vertices, lines, closed) = lines.unwrap_or_default();
+            raster::write_lines_png(path, &vertices, &lines, closed, &raster_options())
+        }
+    };
+    result.map_err(|e| format!("failed to write {}: {}", path.display(), e))
+}
+
+fn render(render: Render) -> Result<(), String> {
+    for symbol in &render.symbols {
+        let name = format!("{}.{}", symbol.file_stem(render.variant), render.format.extension());
+        let path = match &render.output {
+            Some(dir) if render.all_k => dir.join(name),
+            Some(file) => file.clone(),
+            None => PathBuf::from(name)
+        };
+        write(symbol, &path, &render)?;
+        println!("{}", path.display());
+    }
+    Ok(())
+}
+
+/// Runs the `render` subcommand if it was requested, returning the process exit code.
+pub fn try_run() -> Option<i32> {
+    let mut args = env::args().skip(1);
+    if args.next().as_deref() != Some("render") {
+        return None
+    }
+    match parse(args).and_then(|command| command.map(render).transpose()) {
+        Ok(Some(())) => Some(0),
+        Ok(None) => {
+            println!("{}", USAGE);
+            Some(0)
+        },
+        Err(e) => {
+            eprintln!("{}", e);
+            Some(2)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_args(args: &[&str]) -> Result<Option<Render>, String> {
+        parse(args.iter().map(|&arg| arg.to_owned()))
+    }
+
+    fn render(args: &[&str]) -> Render {
+        parse_args(args).unwrap().unwrap()
+    }
+
+    fn error(args: &[&str]) -> String {
+        parse_args(args).err().unwrap()
+    }
+
+    #[test]
+    fn help() {
+        assert!(parse_args(&["--help"]).unwrap().is_none());
+        assert!(parse_args(&["5/2", "-h"]).unwrap().is_none());
+        assert_eq!(error(&[]), USAGE);
+    }
+
+    #[test]
+    fn symbols() {
+        assert_eq!(render(&["5/2"]).symbols, ["5/2".parse::<Symbol>().unwrap()]);
+        assert_eq!(render(&["hypo(5, 2, 2)", "--overlay", "5/2"]).overlay, Some(Polygon::new(5, 2).unwrap()));
+        assert_eq!(render(&["--all-k", "7"]).symbols.len(), 3);
+        assert_eq!(error(&["5/0"]), "5/0: step must be at least 1");
+        assert_eq!(error(&["5", "7"]), format!("unexpected argument '7'\n\n{}", USAGE));
+        assert_eq!(error(&["5", "--all-k", "7"]), "give either a symbol or --all-k, not both");
+        assert_eq!(error(&["--all-k", "2"]), "{2}: a polygon needs at least 3 vertices");
+        assert_eq!(error(&["{4,4}", "--isotoxal", "0.5"]), "--isotoxal and --isogonal only apply to star polygons");
+        assert_eq!(error(&["5/2", "--overlay", "5"]), "--overlay only applies to curves");
+    }
+
+    #[test]
+    fn values() {
+        let render = render(&["5/2", "--size", "64", "--fill", "evenodd", "--isogonal", "0.25", "--color", "red", "--join", "bevel"]);
+        assert_eq!(render.size, 64);
+        assert_eq!(render.fill, Some(FillMode::EvenOdd));
+        assert_eq!(render.variant, Some(Variant::Isogonal(0.25)));
+        assert_eq!(render.style.color, Some(PALETTE.iter().find(|(name, _)| *name == "red").unwrap().1));
+        assert_eq!(render.style.join, Some(Join::Bevel));
+        assert_eq!(error(&["5", "--size"]), "--size needs a value");
+        assert_eq!(error(&["5", "--size", "0"]), "--size must be a positive integer");
+        assert_eq!(error(&["5", "--isotoxal", "2"]), "--isotoxal needs a value from 0 to 1");
+        assert_eq!(error(&["5", "--color", "#12345"]), "--color needs #rrggbb or a colour name, not '#12345'");
+        assert_eq!(error(&["5", "--stroke-width", "0"]), "--stroke-width needs a value from 0.1 to 100");
+        assert_eq!(error(&["5", "--fill", "solid"]), "unknown fill mode 'solid'");
+    }
+
+    #[test]
+    fn format() {
+        assert_eq!(render(&["5"]).format, Format::Png);
+        assert_eq!(render(&["5", "-o", "star.svg"]).format, Format::Svg);
+        assert_eq!(render(&["5", "--format", "svg"]).format, Format::Svg);
+        assert_eq!(render(&["5", "--format", "svg", "-o", "star.svg"]).format, Format::Svg);
+        assert_eq!(render(&["5", "--format", "svg", "-o", "star"]).format, Format::Svg);
+        assert_eq!(render(&["--all-k", "7", "--format", "svg", "-o", "out.png"]).format, Format::Svg);
+        assert_eq!(error(&["5", "--format", "png", "-o", "star.svg"]), "--format png doesn't match the .svg output file");
+        assert_eq!(error(&["5", "--format", "jpg"]), "unknown format 'jpg'");
+    }
+}
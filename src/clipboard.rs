@@ -0,0 +1,50 @@
+//! Reading text from the system clipboard. Browsers only hand it out asynchronously,
+//! so a paste is requested on Ctrl+V and the text is picked up by a later `take_pasted`.
+
+use std::sync::Mutex;
+
+static PASTED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+fn push(text: String) {
+    PASTED.lock().unwrap().push(text)
+}
+
+/// Text pasted since the last call, if any.
+pub fn take_pasted() -> Option<String> {
+    let mut pasted = PASTED.lock().unwrap();
+    if pasted.is_empty() {
+        None
+    } else {
+        Some(pasted.drain(..).collect())
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn request_paste() {
+    match arboard::Clipboard::new().and_then(|mut clipboard| clipboard.get_text()) {
+        Ok(text) => push(text),
+        Err(e) => bevy::log::warn!("could not read the clipboard: {}", e)
+    }
+}
+
+/// winit cancels the browser's own Ctrl+V handling, so no `paste` event arrives; ask `navigator.clipboard` instead.
+#[cfg(target_arch = "wasm32")]
+pub fn request_paste() {
+    use wasm_bindgen::JsCast;
+
+    let read_text = || -> Option<js_sys::Promise> {
+        let navigator = web_sys::window()?.navigator();
+        let clipboard = js_sys::Reflect::get(&navigator, &"clipboard".into()).ok()?;
+        let read_text = js_sys::Reflect::get(&clipboard, &"readText".into()).ok()?.dyn_into::<js_sys::Function>().ok()?;
+        read_text.call0(&clipboard).ok()?.dyn_into::<js_sys::Promise>().ok()
+    };
+    match read_text() {
+        Some(promise) => wasm_bindgen_futures::spawn_local(async move {
+            match wasm_bindgen_futures::JsFuture::from(promise).await {
+                Ok(text) => push(text.as_string().unwrap_or_default()),
+                Err(e) => bevy::log::warn!("could not read the clipboard: {:?}", e)
+            }
+        }),
+        None => bevy::log::warn!("this browser does not allow reading the clipboard")
+    }
+}
@@ -0,0 +1,223 @@
+//! Curves traced by a pen on a rolling circle, whose polygonal limits are star polygons: `hypo(R, r, d)` rolls a
+//! circle of radius `r` inside one of radius `R` with the pen `d` from its centre, `epi(R, r, d)` rolls it
+//! outside, and `rose(n/d)` is the rose `ρ = cos(nθ/d)`.
+//!
+//! With `d = r` the hypotrochoid is a hypocycloid, whose cusps visit the vertices of `{R/r}` in the same order
+//! and touch the unit circle where they are.
+
+use crate::{
+    geometry::{gcd, parse_number, ParseError, ParseErrorKind, Point},
+    symbol::{SymbolError, SymbolErrorKind}
+};
+use std::{
+    f32::consts::{PI, TAU},
+    fmt,
+    str::FromStr
+};
+
+/// Points per turn of either circle, or per petal of a rose.
+const SAMPLES_PER_TURN: usize = 64;
+const MAX_SAMPLES: usize = 50_000;
+/// Largest radius, or `n` or `d` of a rose. Past it the samples are spread too thin to show the curve anyway.
+pub const MAX_PARAMETER: u32 = 1000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CurveKind {
+    Hypotrochoid,
+    Epitrochoid,
+    Rose
+}
+impl CurveKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            CurveKind::Hypotrochoid => "hypotrochoid",
+            CurveKind::Epitrochoid => "epitrochoid",
+            CurveKind::Rose => "rose"
+        }
+    }
+
+    /// What the curve is written as, before its parameters.
+    pub fn prefix(self) -> &'static str {
+        match self {
+            CurveKind::Hypotrochoid => "hypo",
+            CurveKind::Epitrochoid => "epi",
+            CurveKind::Rose => "rose"
+        }
+    }
+}
+
+/// A closed curve scaled to fit the unit circle.
+///
+/// A rose keeps `n` in `big` and `d` in `small`, in lowest terms, and has no pen distance.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Curve {
+    kind: CurveKind,
+    big: u32,
+    small: u32,
+    distance: f32
+}
+impl Curve {
+    pub fn new(kind: CurveKind, big: u32, small: u32, distance: f32) -> Result<Self, CurveError> {
+        if big == 0 || small == 0 {
+            return Err(if kind == CurveKind::Rose { CurveError::ZeroRose } else { CurveError::ZeroRadius })
+        }
+        if big > MAX_PARAMETER || small > MAX_PARAMETER {
+            return Err(CurveError::TooLarge)
+        }
+        if !distance.is_finite() || distance < 0.0 {
+            return Err(CurveError::InvalidDistance)
+        }
+        Ok(match kind {
+            CurveKind::Rose => {
+                let g = gcd(big as usize, small as usize) as u32;
+                Curve {
+                    kind,
+                    big: big/g,
+                    small: small/g,
+                    distance: 0.0
+                }
+            },
+            _ => Curve {
+                kind,
+                big,
+                small,
+                distance
+            }
+        })
+    }
+
+    pub fn kind(&self) -> CurveKind {
+        self.kind
+    }
+
+    /// `R`, or `n` of a rose.
+    pub fn big(&self) -> u32 {
+        self.big
+    }
+
+    /// `r`, or `d` of a rose.
+    pub fn small(&self) -> u32 {
+        self.small
+    }
+
+    pub fn distance(&self) -> f32 {
+        self.distance
+    }
+
+    /// Turns of the rolling circle's centre, or half turns of a rose, before the curve closes.
+    pub fn turns(&self) -> u32 {
+        match self.kind {
+            CurveKind::Rose if self.big % 2 == 1 && self.small % 2 == 1 => self.small,
+            CurveKind::Rose => 2*self.small,
+            _ => self.small/gcd(self.big as usize, self.small as usize) as u32
+        }
+    }
+
+    /// The curve as a closed path, without repeating its first point.
+    pub fn points(&self) -> Vec<Point> {
+        let (big, small, d) = (self.big as f32, self.small as f32, self.distance);
+        let turns = self.turns() as usize;
+        // The faster of the two rotations decides how finely the curve needs sampling.
+        let samples = match self.kind {
+            CurveKind::Rose => SAMPLES_PER_TURN*(turns + self.big as usize*turns/self.small as usize),
+            _ => SAMPLES_PER_TURN*(turns + (self.big + self.small) as usize*turns/self.small as usize)
+        }.min(MAX_SAMPLES);
+        let span = match self.kind {
+            CurveKind::Rose => PI*(turns as f32),
+            _ => TAU*(turns as f32)
+        };
+        let (outer, inner, extent) = match self.kind {
+            CurveKind::Hypotrochoid => (big - small, -(big - small)/small, (big - small).abs() + d),
+            CurveKind::Epitrochoid => (big + small, (big + small)/small, big + small + d),
+            CurveKind::Rose => (0.0, 0.0, 1.0)
+        };
+        let extent = if extent > 0.0 { extent } else { 1.0 };
+        (0..samples).map(|i| {
+            let t = span*(i as f32)/(samples as f32);
+            match self.kind {
+                CurveKind::Rose => {
+                    let radius = (big*t/small).cos();
+                    Point::new(radius*t.cos(), radius*t.sin())
+                },
+                CurveKind::Hypotrochoid => Point::new(
+                    (outer*t.cos() + d*(inner*t).cos())/extent,
+                    (outer*t.sin() + d*(inner*t).sin())/extent
+                ),
+                CurveKind::Epitrochoid => Point::new(
+                    (outer*t.cos() - d*(inner*t).cos())/extent,
+                    (outer*t.sin() - d*(inner*t).sin())/extent
+                )
+            }
+        }).collect()
+    }
+}
+
+/// Prints the distance with at most two decimals, so repeated steps don't show float noise.
+impl fmt::Display for Curve {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            CurveKind::Rose if self.small == 1 => write!(f, "rose({})", self.big),
+            CurveKind::Rose => write!(f, "rose({}/{})", self.big, self.small),
+            kind => {
+                let distance = format!("{:.2}", self.distance);
+                let distance = distance.trim_end_matches('0').trim_end_matches('.');
+                write!(f, "{}({}, {}, {})", kind.prefix(), self.big, self.small, distance)
+            }
+        }
+    }
+}
+/// Accepts `hypo(R, r, d)`, `epi(R, r, d)`, `rose(n/d)` and `rose(n)`, with or without spaces after the commas.
+impl FromStr for Curve {
+    type Err = SymbolError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let open = s.find('(').ok_or_else(|| SymbolError::new(0..s.len(), SymbolErrorKind::UnknownCurve))?;
+        let kind = [CurveKind::Hypotrochoid, CurveKind::Epitrochoid, CurveKind::Rose].into_iter()
+            .find(|kind| *kind.prefix() == s[..open])
+            .ok_or_else(|| SymbolError::new(0..open, SymbolErrorKind::UnknownCurve))?;
+        if !s.ends_with(')') {
+            return Err(SymbolError::new(s.len()..s.len(), SymbolErrorKind::UnclosedParenthesis))
+        }
+        let body = &s[open + 1..s.len() - 1];
+        let separator = if kind == CurveKind::Rose { '/' } else { ',' };
+        // Each argument with the byte offset of its first non-space character.
+        let arguments = body.split(separator).scan(open + 1, |offset, argument| {
+            let start = *offset + argument.len() - argument.trim_start().len();
+            *offset += argument.len() + 1;
+            Some((argument.trim(), start))
+        }).collect::<Vec<(&str, usize)>>();
+        let count_error = SymbolError::new(open + 1..s.len() - 1, SymbolErrorKind::CurveArguments);
+        let number = |(argument, start): (&str, usize)| parse_number(argument, start)
+            .and_then(|n| u32::try_from(n).map_err(|_| ParseError::new(start..start + argument.len(), ParseErrorKind::Overflow)));
+        let (big, small, distance) = match (kind, &arguments[..]) {
+            (CurveKind::Rose, &[n]) => (number(n)?, 1, 0.0),
+            (CurveKind::Rose, &[n, d]) => (number(n)?, number(d)?, 0.0),
+            (CurveKind::Rose, _) => return Err(count_error),
+            (_, &[big, small, (distance, start)]) => {
+                let invalid = SymbolError::new(start..start + distance.len().max(1), CurveError::InvalidDistance.into());
+                (number(big)?, number(small)?, distance.parse::<f32>().map_err(|_| invalid)?)
+            },
+            _ => return Err(count_error)
+        };
+        Curve::new(kind, big, small, distance).map_err(|e| SymbolError::new(open + 1..s.len() - 1, e.into()))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CurveError {
+    ZeroRadius,
+    ZeroRose,
+    InvalidDistance,
+    TooLarge
+}
+impl fmt::Display for CurveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CurveError::ZeroRadius => write!(f, "both radii must be at least 1"),
+            CurveError::ZeroRose => write!(f, "n and d must be at least 1"),
+            CurveError::InvalidDistance => write!(f, "expected a pen distance of 0 or more"),
+            CurveError::TooLarge => write!(f, "radii, n and d can be at most {}", MAX_PARAMETER)
+        }
+    }
+}
+impl std::error::Error for CurveError {}
@@ -0,0 +1,236 @@
+use std::ops::Range;
+
+/// An editable line of text with a caret and an optional selection, plus the last committed value to revert to.
+/// Positions are byte offsets that always fall on character boundaries.
+#[derive(Clone, Debug, Default)]
+pub struct TextField {
+    text: String,
+    caret: usize,
+    anchor: Option<usize>,
+    committed: String
+}
+impl TextField {
+    pub fn new(text: String) -> Self {
+        TextField {
+            caret: text.len(),
+            anchor: None,
+            committed: text.clone(),
+            text
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn caret(&self) -> usize {
+        self.caret
+    }
+
+    /// The selected range, empty at the caret when nothing is selected.
+    pub fn selection(&self) -> Range<usize> {
+        let anchor = self.anchor.unwrap_or(self.caret);
+        anchor.min(self.caret)..anchor.max(self.caret)
+    }
+
+    /// Replaces the whole text, e.g. when the value changes from elsewhere, and commits it.
+    pub fn set(&mut self, text: String) {
+        *self = TextField::new(text)
+    }
+
+    /// Whether the text has changed since it was last committed.
+    pub fn is_edited(&self) -> bool {
+        self.text != self.committed
+    }
+
+    pub fn commit(&mut self) {
+        self.committed = self.text.clone()
+    }
+
+    pub fn revert(&mut self) {
+        self.set(self.committed.clone())
+    }
+
+    /// Inserts `s` at the caret, replacing the selection.
+    pub fn insert(&mut self, s: &str) {
+        self.delete_selection();
+        self.text.insert_str(self.caret, s);
+        self.caret += s.len();
+    }
+
+    pub fn backspace(&mut self) {
+        if !self.delete_selection() {
+            if let Some(previous) = self.previous(self.caret) {
+                self.text.replace_range(previous..self.caret, "");
+                self.caret = previous;
+            }
+        }
+    }
+
+    pub fn delete(&mut self) {
+        if !self.delete_selection() {
+            if let Some(next) = self.next(self.caret) {
+                self.text.replace_range(self.caret..next, "");
+            }
+        }
+    }
+
+    /// Moves one character left, or to the start of the selection when there is one and `select` is off.
+    pub fn left(&mut self, select: bool) {
+        let selection = self.selection();
+        let target = if !select && !selection.is_empty() {
+            selection.start
+        } else {
+            self.previous(self.caret).unwrap_or(self.caret)
+        };
+        self.move_to(target, select)
+    }
+
+    pub fn right(&mut self, select: bool) {
+        let selection = self.selection();
+        let target = if !select && !selection.is_empty() {
+            selection.end
+        } else {
+            self.next(self.caret).unwrap_or(self.caret)
+        };
+        self.move_to(target, select)
+    }
+
+    pub fn home(&mut self, select: bool) {
+        self.move_to(0, select)
+    }
+
+    pub fn end(&mut self, select: bool) {
+        self.move_to(self.text.len(), select)
+    }
+
+    pub fn select_all(&mut self) {
+        self.anchor = Some(0);
+        self.caret = self.text.len();
+    }
+
+    fn move_to(&mut self, caret: usize, select: bool) {
+        if select {
+            self.anchor.get_or_insert(self.caret);
+        } else {
+            self.anchor = None;
+        }
+        self.caret = caret;
+    }
+
+    fn delete_selection(&mut self) -> bool {
+        let selection = self.selection();
+        self.anchor = None;
+        if selection.is_empty() {
+            return false
+        }
+        self.text.replace_range(selection.clone(), "");
+        self.caret = selection.start;
+        true
+    }
+
+    fn previous(&self, i: usize) -> Option<usize> {
+        self.text[..i].char_indices().next_back().map(|(i, _)| i)
+    }
+
+    fn next(&self, i: usize) -> Option<usize> {
+        self.text[i..].chars().next().map(|ch| i + ch.len_utf8())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn caret() {
+        let mut field = TextField::new("{5/2}".to_string());
+        assert_eq!(field.caret(), 5);
+        field.right(false);
+        assert_eq!(field.caret(), 5);
+        field.left(false);
+        field.left(false);
+        field.insert("3");
+        assert_eq!((field.text(), field.caret()), ("{5/32}", 4));
+        field.backspace();
+        field.delete();
+        assert_eq!((field.text(), field.caret()), ("{5/}", 3));
+        field.home(false);
+        field.backspace();
+        field.left(false);
+        assert_eq!((field.text(), field.caret()), ("{5/}", 0));
+        field.end(false);
+        field.delete();
+        assert_eq!((field.text(), field.caret()), ("{5/}", 4));
+    }
+
+    #[test]
+    fn selection() {
+        let mut field = TextField::new("{12/5}".to_string());
+        assert_eq!(field.selection(), 6..6);
+        field.left(true);
+        field.left(true);
+        assert_eq!(field.selection(), 4..6);
+        field.home(true);
+        assert_eq!(field.selection(), 0..6);
+        field.right(false);
+        assert_eq!((field.selection(), field.caret()), (6..6, 6));
+        field.left(true);
+        field.left(false);
+        assert_eq!((field.selection(), field.caret()), (5..5, 5));
+        field.home(false);
+        field.right(true);
+        field.right(true);
+        field.right(true);
+        field.insert("{7");
+        assert_eq!((field.text(), field.caret()), ("{7/5}", 2));
+        field.select_all();
+        field.backspace();
+        assert_eq!((field.text(), field.caret()), ("", 0));
+        field.insert("{5}");
+        field.select_all();
+        field.delete();
+        assert_eq!(field.text(), "");
+    }
+
+    #[test]
+    fn utf8() {
+        let mut field = TextField::new("{5/2}".to_string());
+        field.left(false);
+        field.insert("½é");
+        assert_eq!((field.text(), field.caret()), ("{5/2½é}", 8));
+        field.left(false);
+        assert_eq!(field.caret(), 6);
+        field.left(true);
+        assert_eq!(field.selection(), 4..6);
+        field.delete();
+        assert_eq!((field.text(), field.caret()), ("{5/2é}", 4));
+        field.right(false);
+        field.backspace();
+        assert_eq!((field.text(), field.caret()), ("{5/2}", 4));
+        field.insert("→");
+        field.left(false);
+        field.delete();
+        assert_eq!((field.text(), field.caret()), ("{5/2}", 4));
+    }
+
+    #[test]
+    fn commit_and_revert() {
+        let mut field = TextField::new("{5/2}".to_string());
+        assert!(!field.is_edited());
+        field.backspace();
+        field.insert("3}");
+        assert!(field.is_edited());
+        field.revert();
+        assert_eq!((field.text(), field.caret(), field.is_edited()), ("{5/2}", 5, false));
+        field.select_all();
+        field.insert("{7/3}");
+        field.commit();
+        assert!(!field.is_edited());
+        field.backspace();
+        field.revert();
+        assert_eq!(field.text(), "{7/3}");
+        field.set("{8/3}".to_string());
+        assert_eq!((field.text(), field.caret(), field.is_edited()), ("{8/3}", 5, false));
+    }
+}
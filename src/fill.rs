@@ -0,0 +1,137 @@
+//! Filled regions of a star polygon.
+//!
+//! Every edge of `{n/k}` lies at the same distance from the centre, so any two edges cross on a ray
+//! at a multiple of `π/n`. Between two such rays the edges never cross, which splits the figure into
+//! `2n` sectors where the boundary between winding `j` and `j + 1` is one straight segment.
+
+use crate::geometry::{Point, Polygon};
+use std::f32::consts::PI;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FillMode {
+    EvenOdd,
+    NonZero,
+    /// Every region, coloured by its winding number.
+    Density
+}
+impl FillMode {
+    pub fn fills(self, winding: usize) -> bool {
+        match self {
+            FillMode::EvenOdd => winding % 2 == 1,
+            FillMode::NonZero | FillMode::Density => winding > 0
+        }
+    }
+}
+
+/// Chords crossing a sector, nearest to the centre first, as offsets back from the sector's last chord.
+fn chord_order(k: usize, odd: bool) -> Vec<usize> {
+    let mut order = (0..k).collect::<Vec<usize>>();
+    order.sort_by_key(|&t| (2*k as isize - 4*t as isize - if odd { 3 } else { 1 }).abs());
+    order
+}
+
+struct Levels {
+    n: usize,
+    k: usize,
+    even: Vec<usize>,
+    odd: Vec<usize>
+}
+impl Levels {
+    fn new(polygon: &Polygon) -> Self {
+        let k = polygon.density();
+        Levels {
+            n: polygon.n(),
+            k,
+            even: chord_order(k, false),
+            odd: chord_order(k, true)
+        }
+    }
+
+    /// Where the ray at `s·π/n` meets the boundary of the region with winding at least `j`,
+    /// using the chord that bounds that region in sector `sector`.
+    fn point(&self, j: usize, sector: usize, s: usize) -> Point {
+        if j > self.k {
+            return Point::new(0.0, 0.0)
+        }
+        let order = if sector % 2 == 1 { &self.odd } else { &self.even };
+        let chord = (sector/2 + self.n - order[self.k - j]) % self.n;
+        let normal = PI*((2*chord + self.k) as f32)/(self.n as f32);
+        let angle = PI*(s as f32)/(self.n as f32);
+        let rho = (PI*(self.k as f32)/(self.n as f32)).cos()/(angle - normal).cos();
+        Point::new(rho*angle.cos(), rho*angle.sin())
+    }
+
+    fn area(&self, j: usize) -> f32 {
+        if j == 0 || j > self.k {
+            return 0.0
+        }
+        let (a, b) = (self.point(j, 0, 0), self.point(j, 0, 1));
+        (self.n as f32)*(a.x*b.y - a.y*b.x)
+    }
+}
+
+/// Boundary of the region with winding at least `j`, a star-shaped `2n`-gon around the centre.
+/// Level 1 is the simple outline of the whole figure. Empty if `j` is 0 or above [`Polygon::density`].
+pub fn level_outline(polygon: &Polygon, j: usize) -> Vec<Point> {
+    let levels = Levels::new(polygon);
+    if j == 0 || j > levels.k {
+        return Vec::new()
+    }
+    (0..2*levels.n).map(|s| levels.point(j, s, s)).collect()
+}
+
+/// Area enclosed by [`level_outline`]. The outline is made of `2n` congruent triangles around the centre.
+pub fn level_area(polygon: &Polygon, j: usize) -> f32 {
+    Levels::new(polygon).area(j)
+}
+
+/// Area covered when filling with `mode`; [`FillMode::Density`] covers the same area as [`FillMode::NonZero`].
+pub fn area(polygon: &Polygon, mode: FillMode) -> f32 {
+    let levels = Levels::new(polygon);
+    (1..=levels.k).filter(|&j| mode.fills(j)).map(|j| match mode {
+        FillMode::EvenOdd => levels.area(j) - levels.area(j + 1),
+        FillMode::NonZero | FillMode::Density => if j == 1 { levels.area(1) } else { 0.0 }
+    }).fold(0.0, |a, b| a + b)
+}
+
+/// A convex piece of the figure with constant winding number, listed counter-clockwise.
+/// Pieces touching the centre are triangles with the last two points at the origin.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Region {
+    pub winding: usize,
+    pub points: [Point; 4]
+}
+impl Region {
+    pub fn triangles(&self) -> [[Point; 3]; 2] {
+        let [a, b, c, d] = self.points;
+        [[a, b, c], [a, c, d]]
+    }
+}
+
+/// Every region with winding number `j` for which `filter(j)` holds.
+pub fn regions(polygon: &Polygon, filter: impl Fn(usize) -> bool) -> Vec<Region> {
+    let levels = Levels::new(polygon);
+    let mut regions = Vec::new();
+    for j in (1..=levels.k).filter(|&j| filter(j)) {
+        for sector in 0..2*levels.n {
+            let next = (sector + 1) % (2*levels.n);
+            regions.push(Region {
+                winding: j,
+                points: [
+                    levels.point(j, sector, sector),
+                    levels.point(j, sector, next),
+                    levels.point(j + 1, sector, next),
+                    levels.point(j + 1, sector, sector)
+                ]
+            });
+        }
+    }
+    regions
+}
+
+/// Blue for the outermost regions through to yellow for the densest core, as sRGB.
+pub fn density_color(winding: usize, density: usize) -> [f32; 3] {
+    let t = if density > 1 { (winding - 1) as f32/(density - 1) as f32 } else { 0.0 };
+    let (from, to) = ([0.15, 0.25, 0.6], [1.0, 0.85, 0.3]);
+    [0, 1, 2].map(|i| from[i] + (to[i] - from[i])*t)
+}
@@ -0,0 +1,569 @@
+use std::{
+    f32::consts::{PI, TAU},
+    fmt,
+    ops::Range,
+    str::FromStr
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Point {
+    pub x: f32,
+    pub y: f32
+}
+impl Point {
+    pub fn new(x: f32, y: f32) -> Self {
+        Point {
+            x,
+            y
+        }
+    }
+
+    pub fn scale(self, s: f32) -> Self {
+        Point::new(self.x*s, self.y*s)
+    }
+}
+
+/// Most vertices a polygon can have, compounds included, so a typo can't ask for billions of points.
+pub const MAX_VERTICES: usize = 1000;
+
+/// A regular star polygon `{n/k}`: `n` points on the unit circle, each joined to the point `k` steps ahead.
+///
+/// Only constructible through [`Polygon::new`] or parsing, so `3 <= n <= MAX_VERTICES` and `0 < k < n` always hold.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Polygon {
+    n: usize,
+    k: usize
+}
+impl Polygon {
+    pub fn new(n: usize, k: usize) -> Result<Self, PolygonError> {
+        if n < 3 {
+            Err(PolygonError::TooFewVertices)
+        } else if n > MAX_VERTICES {
+            Err(PolygonError::TooManyVertices)
+        } else if k == 0 {
+            Err(PolygonError::ZeroStep)
+        } else if k >= n {
+            Err(PolygonError::StepTooLarge)
+        } else {
+            Ok(Polygon {
+                n,
+                k
+            })
+        }
+    }
+
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    pub fn k(&self) -> usize {
+        self.k
+    }
+
+    /// Points on the unit circle, starting at `(1, 0)` and going counter-clockwise.
+    pub fn vertices(&self) -> Vec<Point> {
+        (0..self.n).map(|i| {
+            let (sin, cos) = (TAU*(i as f32)/(self.n as f32)).sin_cos();
+            Point::new(cos, sin)
+        }).collect()
+    }
+
+    /// Pairs of indices into [`Polygon::vertices`], one per edge.
+    pub fn edges(&self) -> Vec<[usize; 2]> {
+        (0..self.n).map(|i| [i, (i + self.k) % self.n]).collect()
+    }
+
+    /// The vertices of each component in the order a pen visits them, component `c` starting from vertex `c`.
+    pub fn cycles(&self) -> Vec<Vec<usize>> {
+        let m = self.components();
+        (0..m).map(|c| (0..self.n/m).map(|step| (c + step*self.k) % self.n).collect()).collect()
+    }
+
+    /// The points of each of [`Polygon::cycles`].
+    pub fn paths(&self) -> Vec<Vec<Point>> {
+        let vertices = self.vertices();
+        self.cycles().into_iter().map(|cycle| cycle.into_iter().map(|i| vertices[i]).collect()).collect()
+    }
+
+    /// Number of separate closed paths; `{6/2}` is the compound `2{3}` of two triangles.
+    pub fn components(&self) -> usize {
+        gcd(self.n, self.k)
+    }
+
+    /// `(n, k)` of every component. `n` is 2 for compounds of digons such as `{6/3}`, which is why this is not a `Polygon`.
+    pub fn component(&self) -> (usize, usize) {
+        let m = self.components();
+        (self.n/m, self.k/m)
+    }
+
+    /// Index of the component vertex `i` belongs to; components are rotated copies offset by one vertex each.
+    pub fn component_of(&self, i: usize) -> usize {
+        i % self.components()
+    }
+
+    /// Winding number around the centre, ignoring orientation. Compounds of digons enclose nothing.
+    pub fn density(&self) -> usize {
+        let k = self.k.min(self.n - self.k);
+        if 2*k == self.n { 0 } else { k }
+    }
+
+    /// Points where two edges cross, other than the vertices themselves.
+    ///
+    /// Every edge is at the same distance from the centre, so two edges cross on the ray bisecting them,
+    /// which is at a multiple of `π/n`. Each point is listed once, ray by ray, innermost first.
+    pub fn intersections(&self) -> Vec<Point> {
+        self.intersections_on(0..2*self.n)
+    }
+
+    /// One point from each orbit of [`Polygon::intersections`] under rotation by `2π/n`.
+    pub fn intersection_orbits(&self) -> Vec<Point> {
+        self.intersections_on(0..2)
+    }
+
+    /// Intersections on the rays at `s·π/n` for `s` in `rays`, for `0 < δ < k` of the same parity as `k - s`.
+    fn intersections_on(&self, rays: Range<usize>) -> Vec<Point> {
+        let k = self.density();
+        if k == 0 {
+            return vec![Point::new(0.0, 0.0)]
+        }
+        rays.flat_map(|s| {
+            let first = if (s + k) % 2 == 1 { 1 } else { 2 };
+            (first..k).step_by(2).map(move |delta| self.intersection(s, delta))
+        }).collect()
+    }
+
+    /// Where the two edges whose normals are `δ·π/n` either side of the ray at `s·π/n` cross it,
+    /// at distance `cos(kπ/n)/cos(δπ/n)` from the centre.
+    fn intersection(&self, s: usize, delta: usize) -> Point {
+        let n = self.n as f32;
+        let rho = (PI*(self.density() as f32)/n).cos()/(PI*(delta as f32)/n).cos();
+        let angle = PI*(s as f32)/n;
+        Point::new(rho*angle.cos(), rho*angle.sin())
+    }
+
+    /// The simple polygon around the figure without its interior crossings: every vertex followed by the
+    /// outermost intersection before the next one. Convex polygons are their own outline; compounds of digons have none.
+    pub fn outline(&self) -> Vec<Point> {
+        match self.density() {
+            0 => Vec::new(),
+            1 => self.vertices(),
+            k => self.vertices().into_iter().enumerate().flat_map(|(i, v)| [v, self.intersection(2*i + 1, k - 1)]).collect()
+        }
+    }
+
+    /// The next distinct shape with the same `n`, cycling through `k = 1..=n/2` forwards or backwards.
+    /// With `coprime`, compounds are skipped; `k = 1` always qualifies.
+    pub fn cycle_k(&self, forward: bool, coprime: bool) -> Polygon {
+        let count = self.n/2;
+        let mut k = self.k.min(self.n - self.k);
+        loop {
+            k = if forward { k % count + 1 } else { (k + count - 2) % count + 1 };
+            if !coprime || gcd(self.n, k) == 1 {
+                return Polygon {
+                    n: self.n,
+                    k
+                }
+            }
+        }
+    }
+
+    /// `{n+1/k}` or `{n-1/k}`, stopping at 3 and [`MAX_VERTICES`] vertices. `k` is lowered when it no longer fits,
+    /// and with `coprime` until the result is a single path.
+    pub fn step_n(&self, up: bool, coprime: bool) -> Polygon {
+        let n = if up { (self.n + 1).min(MAX_VERTICES) } else { (self.n - 1).max(3) };
+        let k = (1..=self.k.min(n - 1)).rev().find(|&k| !coprime || gcd(n, k) == 1).unwrap_or(1);
+        Polygon {
+            n,
+            k
+        }
+    }
+}
+impl fmt::Display for Polygon {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let m = self.components();
+        let (n, k) = self.component();
+        if m > 1 {
+            write!(f, "{}", m)?
+        }
+        if k == 1 {
+            write!(f, "{{{}}}", n)
+        } else {
+            write!(f, "{{{}/{}}}", n, k)
+        }
+    }
+}
+
+/// The edges of `{n/k}` for a real `k`: vertex `i` joined to the point on the unit circle `k` vertices further round.
+/// Whole values of `k` give the edges of [`Polygon`]; in between, the far ends fall between the vertices.
+pub fn fractional_edges(n: usize, k: f32) -> Vec<[Point; 2]> {
+    let point = |i: f32| {
+        let (sin, cos) = (TAU*i/(n as f32)).sin_cos();
+        Point::new(cos, sin)
+    };
+    (0..n).map(|i| [point(i as f32), point(i as f32 + k)]).collect()
+}
+
+pub fn gcd(mut a: usize, mut b: usize) -> usize {
+    while b != 0 {
+        (a, b) = (b, a % b)
+    }
+    a
+}
+/// Accepts `n`, `n/k`, `{n/k}` and compounds `m{n/k}`, which are the same as `{mn/mk}`.
+impl FromStr for Polygon {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err(ParseError::new(0..0, ParseErrorKind::Empty))
+        }
+        let open = match s.find('{') {
+            Some(open) => open,
+            None => return parse_body(s, 0, 1)
+        };
+        let m = if open == 0 { 1 } else { parse_number(&s[..open], 0)? };
+        if m == 0 {
+            return Err(ParseError::new(0..open, ParseErrorKind::ZeroComponents))
+        }
+        let close = match s[open + 1..].find('}') {
+            Some(i) => open + 1 + i,
+            None => return Err(ParseError::new(s.len()..s.len(), ParseErrorKind::UnclosedBrace))
+        };
+        if let Some(ch) = s[close + 1..].chars().next() {
+            return Err(ParseError::new(close + 1..close + 1 + ch.len_utf8(), ParseErrorKind::InvalidCharacter(ch)))
+        }
+        parse_body(&s[open + 1..close], open + 1, m)
+    }
+}
+
+/// Parses `n` or `n/k` starting at byte `offset` of the whole input, as `m` rotated copies.
+pub(crate) fn parse_body(s: &str, offset: usize, m: usize) -> Result<Polygon, ParseError> {
+    if s.is_empty() {
+        return Err(ParseError::new(offset..offset, ParseErrorKind::Empty))
+    }
+    let slashes = s.match_indices('/').map(|(i, _)| offset + i).collect::<Vec<usize>>();
+    let end = offset + s.len();
+    if slashes.len() > 1 {
+        return Err(ParseError::new(slashes[1]..end, ParseErrorKind::TooManySlashes))
+    }
+    let (n, k) = match slashes.first() {
+        Some(&i) => (parse_number(&s[..i - offset], offset)?, parse_number(&s[i + 1 - offset..], i + 1)?),
+        None => (parse_number(s, offset)?, 1)
+    };
+    let n_span = offset..slashes.first().copied().unwrap_or(end);
+    let k_span = slashes.first().map_or(n_span.clone(), |&i| i + 1..end);
+    if n < 2 {
+        return Err(ParseError::new(n_span, ParseErrorKind::TooFewVertices))
+    }
+    let (total_n, total_k) = match (n.checked_mul(m), k.checked_mul(m)) {
+        (Some(n), Some(k)) => (n, k),
+        _ => return Err(ParseError::new(0..end, ParseErrorKind::Overflow))
+    };
+    Polygon::new(total_n, total_k).map_err(|e| {
+        let span = match e {
+            PolygonError::TooFewVertices | PolygonError::TooManyVertices => n_span,
+            _ => k_span
+        };
+        ParseError::new(span, e.into())
+    })
+}
+
+pub(crate) fn parse_number(s: &str, offset: usize) -> Result<usize, ParseError> {
+    if s.is_empty() {
+        return Err(ParseError::new(offset..offset, ParseErrorKind::Empty))
+    }
+    if let Some((i, ch)) = s.char_indices().find(|(_, ch)| !ch.is_ascii_digit()) {
+        return Err(ParseError::new(offset + i..offset + i + ch.len_utf8(), ParseErrorKind::InvalidCharacter(ch)))
+    }
+    s.parse().map_err(|_| ParseError::new(offset..offset + s.len(), ParseErrorKind::Overflow))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PolygonError {
+    TooFewVertices,
+    TooManyVertices,
+    ZeroStep,
+    StepTooLarge
+}
+impl fmt::Display for PolygonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PolygonError::TooFewVertices => write!(f, "a polygon needs at least 3 vertices"),
+            PolygonError::TooManyVertices => write!(f, "a polygon can have at most {} vertices", MAX_VERTICES),
+            PolygonError::ZeroStep => write!(f, "step must be at least 1"),
+            PolygonError::StepTooLarge => write!(f, "step must be smaller than the vertex count")
+        }
+    }
+}
+impl std::error::Error for PolygonError {}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    Empty,
+    InvalidCharacter(char),
+    TooManySlashes,
+    UnclosedBrace,
+    ZeroComponents,
+    Overflow,
+    TooFewVertices,
+    TooManyVertices,
+    ZeroStep,
+    StepTooLarge
+}
+impl From<PolygonError> for ParseErrorKind {
+    fn from(e: PolygonError) -> Self {
+        match e {
+            PolygonError::TooFewVertices => ParseErrorKind::TooFewVertices,
+            PolygonError::TooManyVertices => ParseErrorKind::TooManyVertices,
+            PolygonError::ZeroStep => ParseErrorKind::ZeroStep,
+            PolygonError::StepTooLarge => ParseErrorKind::StepTooLarge
+        }
+    }
+}
+
+/// Why a Schläfli symbol failed to parse, and the byte range of the input it concerns.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub span: Range<usize>,
+    pub kind: ParseErrorKind
+}
+impl ParseError {
+    pub fn new(span: Range<usize>, kind: ParseErrorKind) -> Self {
+        ParseError {
+            span,
+            kind
+        }
+    }
+}
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            ParseErrorKind::Empty => write!(f, "expected a number"),
+            ParseErrorKind::InvalidCharacter(ch) => write!(f, "unexpected character '{}'", ch),
+            ParseErrorKind::TooManySlashes => write!(f, "only one '/' is allowed"),
+            ParseErrorKind::UnclosedBrace => write!(f, "missing closing '}}'"),
+            ParseErrorKind::ZeroComponents => write!(f, "a compound needs at least 1 component"),
+            ParseErrorKind::Overflow => write!(f, "number is too large"),
+            ParseErrorKind::TooFewVertices => PolygonError::TooFewVertices.fmt(f),
+            ParseErrorKind::TooManyVertices => PolygonError::TooManyVertices.fmt(f),
+            ParseErrorKind::ZeroStep => PolygonError::ZeroStep.fmt(f),
+            ParseErrorKind::StepTooLarge => PolygonError::StepTooLarge.fmt(f)
+        }
+    }
+}
+impl std::error::Error for ParseError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(s: &str) -> (Range<usize>, ParseErrorKind) {
+        let e = s.parse::<Polygon>().unwrap_err();
+        (e.span, e.kind)
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(error(""), (0..0, ParseErrorKind::Empty));
+        assert_eq!(error("{}"), (1..1, ParseErrorKind::Empty));
+        assert_eq!(error("5/"), (2..2, ParseErrorKind::Empty));
+    }
+
+    #[test]
+    fn non_numeric() {
+        assert_eq!(error("x"), (0..1, ParseErrorKind::InvalidCharacter('x')));
+        assert_eq!(error("5/2.5"), (3..4, ParseErrorKind::InvalidCharacter('.')));
+        assert_eq!(error("{5/é}"), (3..5, ParseErrorKind::InvalidCharacter('é')));
+        assert_eq!(error("{5/2}x"), (5..6, ParseErrorKind::InvalidCharacter('x')));
+    }
+
+    #[test]
+    fn too_many_slashes() {
+        assert_eq!(error("5/2/1"), (3..5, ParseErrorKind::TooManySlashes));
+        assert_eq!(error("{7/2/1/1}"), (4..8, ParseErrorKind::TooManySlashes));
+    }
+
+    #[test]
+    fn overflow() {
+        assert_eq!(error("99999999999999999999999/2"), (0..23, ParseErrorKind::Overflow));
+        assert_eq!(error("5/99999999999999999999999"), (2..25, ParseErrorKind::Overflow));
+        assert_eq!(error("9999999999999999999{5/2}"), (0..23, ParseErrorKind::Overflow));
+    }
+
+    #[test]
+    fn too_few_vertices() {
+        assert_eq!(error("1"), (0..1, ParseErrorKind::TooFewVertices));
+        assert_eq!(error("2/1"), (0..1, ParseErrorKind::TooFewVertices));
+        assert_eq!(error("{2}"), (1..2, ParseErrorKind::TooFewVertices));
+    }
+
+    #[test]
+    fn too_many_vertices() {
+        assert_eq!(error("4000000000/3"), (0..10, ParseErrorKind::TooManyVertices));
+        assert_eq!(error("{1001/2}"), (1..5, ParseErrorKind::TooManyVertices));
+        assert_eq!(error("3{334}"), (2..5, ParseErrorKind::TooManyVertices));
+        assert!("{1000/499}".parse::<Polygon>().is_ok());
+        assert!("2{500}".parse::<Polygon>().is_ok());
+        assert_eq!(Polygon::new(MAX_VERTICES, 1).unwrap().step_n(true, false), Polygon::new(MAX_VERTICES, 1).unwrap());
+    }
+
+    #[test]
+    fn zero_step() {
+        assert_eq!(error("5/0"), (2..3, ParseErrorKind::ZeroStep));
+        assert_eq!(error("{5/0}"), (3..4, ParseErrorKind::ZeroStep));
+    }
+
+    #[test]
+    fn step_too_large() {
+        assert_eq!(error("5/5"), (2..3, ParseErrorKind::StepTooLarge));
+        assert_eq!(error("{5/7}"), (3..4, ParseErrorKind::StepTooLarge));
+    }
+
+    #[test]
+    fn invalid_polygons() {
+        assert_eq!(Polygon::new(0, 1), Err(PolygonError::TooFewVertices));
+        assert_eq!(Polygon::new(2, 1), Err(PolygonError::TooFewVertices));
+        assert_eq!(Polygon::new(MAX_VERTICES + 1, 1), Err(PolygonError::TooManyVertices));
+        assert_eq!(Polygon::new(5, 0), Err(PolygonError::ZeroStep));
+        assert_eq!(Polygon::new(5, 5), Err(PolygonError::StepTooLarge));
+        assert_eq!(error("0").1, ParseErrorKind::TooFewVertices);
+        assert_eq!(error("2").1, ParseErrorKind::TooFewVertices);
+        assert_eq!(error("5/0").1, ParseErrorKind::ZeroStep);
+        assert_eq!(error("5/5").1, ParseErrorKind::StepTooLarge);
+    }
+
+    #[test]
+    fn canonical_form() {
+        let canonical = |s: &str| s.parse::<Polygon>().unwrap().to_string();
+        assert_eq!(canonical("5"), "{5}");
+        assert_eq!(canonical("5/2"), "{5/2}");
+        assert_eq!(canonical("6/2"), "2{3}");
+        assert_eq!(canonical("3{10/3}"), "3{10/3}");
+        assert_eq!(canonical("2{6/2}"), "4{3}");
+        assert_eq!(canonical("6/3"), "3{2}");
+    }
+
+    #[test]
+    fn round_trip() {
+        for s in ["{5}", "{7/3}", "2{3}", "3{10/3}", "4{3}", "3{2}"] {
+            let polygon = s.parse::<Polygon>().unwrap();
+            assert_eq!(polygon.to_string(), s);
+            assert_eq!(polygon.to_string().parse::<Polygon>(), Ok(polygon));
+        }
+    }
+
+    fn radii(points: &[Point]) -> Vec<f32> {
+        let mut radii = points.iter().map(|p| p.x.hypot(p.y)).collect::<Vec<f32>>();
+        radii.sort_by(f32::total_cmp);
+        radii.dedup_by(|a, b| (*a - *b).abs() < 1e-5);
+        radii
+    }
+
+    fn assert_close(actual: &[f32], expected: &[f32]) {
+        assert_eq!(actual.len(), expected.len(), "{:?} != {:?}", actual, expected);
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-5, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn intersections() {
+        let ratio = |n: f32, k: f32, delta: f32| (PI*k/n).cos()/(PI*delta/n).cos();
+        let pentagram = "5/2".parse::<Polygon>().unwrap().intersections();
+        assert_eq!(pentagram.len(), 5);
+        assert_close(&radii(&pentagram), &[ratio(5.0, 2.0, 1.0)]);
+        assert!((radii(&pentagram)[0] - 0.381_966).abs() < 1e-5);
+        let heptagram = "7/3".parse::<Polygon>().unwrap().intersections();
+        assert_eq!(heptagram.len(), 14);
+        assert_close(&radii(&heptagram), &[ratio(7.0, 3.0, 1.0), ratio(7.0, 3.0, 2.0)]);
+        let hexagram = "6/2".parse::<Polygon>().unwrap().intersections();
+        assert_eq!(hexagram.len(), 6);
+        assert_close(&radii(&hexagram), &[ratio(6.0, 2.0, 1.0)]);
+        assert_eq!("6/3".parse::<Polygon>().unwrap().intersections(), vec![Point::new(0.0, 0.0)]);
+    }
+
+    #[test]
+    fn intersection_orbits() {
+        let orbits = |s: &str| s.parse::<Polygon>().unwrap().intersection_orbits().len();
+        assert_eq!(orbits("5"), 0);
+        assert_eq!(orbits("5/2"), 1);
+        assert_eq!(orbits("7/3"), 2);
+        assert_eq!(orbits("6/2"), 1);
+        assert_eq!(orbits("9/4"), 3);
+        let polygon = "7/3".parse::<Polygon>().unwrap();
+        // Every intersection is a rotation of one in its orbit.
+        let step = TAU/7.0;
+        for p in polygon.intersections() {
+            assert!(polygon.intersection_orbits().iter().any(|q| (0..7).any(|i| {
+                let (sin, cos) = (step*(i as f32)).sin_cos();
+                (q.x*cos - q.y*sin - p.x).hypot(q.x*sin + q.y*cos - p.y) < 1e-5
+            })));
+        }
+    }
+
+    #[test]
+    fn outline() {
+        let outline = "5/2".parse::<Polygon>().unwrap().outline();
+        assert_eq!(outline.len(), 10);
+        for (i, p) in outline.iter().enumerate() {
+            let expected = if i % 2 == 0 { 1.0 } else { 0.381_966 };
+            assert!((p.x.hypot(p.y) - expected).abs() < 1e-5, "point {} is {:?}", i, p);
+        }
+        assert_eq!("5".parse::<Polygon>().unwrap().outline(), "5".parse::<Polygon>().unwrap().vertices());
+        assert!("6/3".parse::<Polygon>().unwrap().outline().is_empty());
+    }
+    fn polygon(n: usize, k: usize) -> Polygon {
+        Polygon::new(n, k).unwrap()
+    }
+
+    #[test]
+    fn cycle_k() {
+        let ks = |n: usize, forward: bool, coprime: bool| {
+            let mut p = polygon(n, 1);
+            (0..5).map(|_| {
+                p = p.cycle_k(forward, coprime);
+                p.k()
+            }).collect::<Vec<usize>>()
+        };
+        assert_eq!(ks(7, true, false), [2, 3, 1, 2, 3]);
+        assert_eq!(ks(7, false, false), [3, 2, 1, 3, 2]);
+        assert_eq!(ks(8, true, false), [2, 3, 4, 1, 2]);
+        assert_eq!(ks(8, true, true), [3, 1, 3, 1, 3]);
+        assert_eq!(ks(12, false, true), [5, 1, 5, 1, 5]);
+        assert_eq!(ks(4, true, true), [1, 1, 1, 1, 1]);
+        // `{7/5}` is the same shape as `{7/2}`, so the next one is `{7/3}`.
+        assert_eq!(polygon(7, 5).cycle_k(true, false), polygon(7, 3));
+    }
+
+    #[test]
+    fn step_n() {
+        assert_eq!(polygon(7, 3).step_n(false, false), polygon(6, 3));
+        assert_eq!(polygon(7, 3).step_n(false, true), polygon(6, 1));
+        assert_eq!(polygon(5, 4).step_n(false, true), polygon(4, 3));
+        assert_eq!(polygon(5, 2).step_n(true, true), polygon(6, 1));
+        assert_eq!(polygon(3, 1).step_n(false, true), polygon(3, 1));
+        for n in 3..=20 {
+            for k in 1..n {
+                for (up, coprime) in [(false, false), (false, true), (true, false), (true, true)] {
+                    let p = polygon(n, k).step_n(up, coprime);
+                    assert!(p.k() >= 1 && p.k() < p.n() && p.k() <= k, "{{{}/{}}} stepped to {:?}", n, k, p);
+                    assert!(!coprime || p.components() == 1, "{{{}/{}}} stepped to {}", n, k, p);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn whole_fractional_edges() {
+        for (n, k) in [(5, 1), (5, 2), (7, 3), (6, 2), (8, 5)] {
+            let polygon = polygon(n, k);
+            let vertices = polygon.vertices();
+            let fractional = fractional_edges(n, k as f32);
+            assert_eq!(fractional.len(), polygon.edges().len());
+            for ([a, b], [p, q]) in polygon.edges().into_iter().zip(fractional) {
+                assert!((vertices[a].x - p.x).hypot(vertices[a].y - p.y) < 1e-5, "{}", polygon);
+                assert!((vertices[b].x - q.x).hypot(vertices[b].y - q.y) < 1e-5, "{}", polygon);
+            }
+        }
+    }
+}
@@ -0,0 +1,127 @@
+/// How many states are kept before the oldest ones are dropped.
+const LIMIT: usize = 100;
+
+/// A linear undo stack: recording after an undo discards everything that could have been redone.
+#[derive(Clone, Debug)]
+pub struct History<T> {
+    states: Vec<T>,
+    current: usize
+}
+impl<T> Default for History<T> {
+    fn default() -> Self {
+        History {
+            states: Vec::new(),
+            current: 0
+        }
+    }
+}
+impl<T> History<T> {
+    pub fn states(&self) -> &[T] {
+        &self.states
+    }
+
+    /// Index of the state that is shown, meaningless while the history is empty.
+    pub fn position(&self) -> usize {
+        self.current
+    }
+
+    pub fn current(&self) -> Option<&T> {
+        self.states.get(self.current)
+    }
+
+    pub fn push(&mut self, state: T) {
+        self.states.truncate(self.current + 1);
+        self.states.push(state);
+        if self.states.len() > LIMIT {
+            self.states.remove(0);
+        }
+        self.current = self.states.len() - 1;
+    }
+
+    /// Overwrites the current state, folding a minor change into it instead of recording a new step.
+    pub fn replace(&mut self, state: T) {
+        match self.states.get_mut(self.current) {
+            Some(current) => *current = state,
+            None => self.push(state)
+        }
+    }
+
+    pub fn undo(&mut self) -> Option<&T> {
+        self.jump(self.current.checked_sub(1)?)
+    }
+
+    pub fn redo(&mut self) -> Option<&T> {
+        self.jump(self.current + 1)
+    }
+
+    pub fn jump(&mut self, i: usize) -> Option<&T> {
+        if i < self.states.len() {
+            self.current = i;
+        }
+        self.states.get(i)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(states: impl IntoIterator<Item = usize>) -> History<usize> {
+        let mut history = History::default();
+        for state in states {
+            history.push(state);
+        }
+        history
+    }
+
+    #[test]
+    fn undo_redo() {
+        let mut history = history(0..3);
+        assert_eq!(history.current(), Some(&2));
+        assert_eq!(history.redo(), None);
+        assert_eq!(history.undo(), Some(&1));
+        assert_eq!(history.undo(), Some(&0));
+        assert_eq!(history.undo(), None);
+        assert_eq!(history.position(), 0);
+        assert_eq!(history.redo(), Some(&1));
+        assert_eq!(history.current(), Some(&1));
+        assert_eq!(History::<usize>::default().undo(), None);
+        assert_eq!(History::<usize>::default().redo(), None);
+    }
+
+    #[test]
+    fn push_truncates() {
+        let mut history = history(0..4);
+        history.undo();
+        history.undo();
+        history.push(9);
+        assert_eq!(history.states(), [0, 1, 9]);
+        assert_eq!(history.position(), 2);
+        assert_eq!(history.redo(), None);
+    }
+
+    #[test]
+    fn limit() {
+        let mut history = history(0..LIMIT + 5);
+        assert_eq!(history.states().len(), LIMIT);
+        assert_eq!(history.states()[0], 5);
+        assert_eq!(history.current(), Some(&(LIMIT + 4)));
+        assert_eq!(history.jump(0), Some(&5));
+        assert_eq!(history.jump(LIMIT), None);
+        assert_eq!(history.position(), 0);
+    }
+
+    #[test]
+    fn replace() {
+        let mut history = History::default();
+        history.replace(0);
+        assert_eq!(history.states(), [0]);
+        history.push(1);
+        history.push(2);
+        history.undo();
+        history.replace(7);
+        assert_eq!(history.states(), [0, 7, 2]);
+        assert_eq!(history.position(), 1);
+        assert_eq!(history.redo(), Some(&2));
+    }
+}
@@ -0,0 +1,14 @@
+pub mod chords;
+pub mod curves;
+pub mod fill;
+pub mod geometry;
+pub mod morph;
+pub mod operators;
+pub mod palette;
+pub mod properties;
+pub mod raster;
+pub mod stroke;
+pub mod svg;
+pub mod symbol;
+pub mod tiling;
+pub mod variant;
@@ -1,121 +1,544 @@
+#[cfg(not(target_arch = "wasm32"))]
+mod cli;
+mod clipboard;
+mod field;
+mod history;
+
 use bevy::{
     prelude::*,
-    sprite::{MaterialMesh2dBundle, Mesh2dHandle},
-    window::PresentMode,
-    input::mouse::{MouseWheel, MouseScrollUnit},
-    render::mesh::{self, PrimitiveTopology},
+    sprite::MaterialMesh2dBundle,
+    window::{PresentMode, RequestRedraw},
+    input::{
+        ButtonState,
+        keyboard::KeyboardInput,
+        mouse::{MouseWheel, MouseScrollUnit}
+    },
+    render::{
+        mesh::{self, MeshVertexAttribute, PrimitiveTopology, VertexAttributeValues},
+        render_resource::{Extent3d, TextureDimension, TextureFormat, VertexFormat}
+    },
     winit::WinitSettings
 };
-use std::{
-    f32::consts::TAU,
-    str::FromStr
+use shaper_2d::{
+    chords::{self, ChordRule},
+    curves::{Curve, CurveKind},
+    fill::{self, FillMode},
+    geometry::{self, Point, Polygon},
+    morph::{Easing, Morph},
+    operators::Derived,
+    palette::{component_color, PALETTE},
+    properties::Properties,
+    stroke::{self, Join},
+    symbol::Symbol,
+    tiling::{Patch, Tiling},
+    variant::Variant
+};
+#[cfg(not(target_arch = "wasm32"))]
+use shaper_2d::{
+    palette::{component_colors, hex, rgba8},
+    raster::{self, RasterOptions},
+    svg::{self, SvgOptions}
 };
+use field::TextField;
+use history::History;
+use std::f32::consts::TAU;
 
 struct Redraw;
 
-#[derive(Clone)]
-struct Polygon {
-    n: usize,
-    k: usize
-}
-impl ToString for Polygon {
-    fn to_string(&self) -> String {
-        if self.k == 1 {
-            format!("{}", self.n)
-        } else {
-            format!("{}/{}", self.n, self.k)
-        }
-    }
-}
-impl FromStr for Polygon {
-    type Err = usize;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let sections = s.split('/').collect::<Vec<&str>>();
-        if sections.len() == 1 {
-            match sections[0].parse::<usize>() {
-                Ok(n) => Ok(Polygon {
-                    n,
-                    k: 1
-                }),
-                Err(_) => Err(0)
-            }
-        } else if sections.len() == 2 {
-            if let Ok(i) = sections[0].parse::<usize>() {
-                let n = i;
-                if let Ok(i) = sections[1].parse::<usize>() {
-                    let k = i;
-                    Ok(Polygon {
-                        n,
-                        k
-                    })
-                } else {
-                    Err(s.find('/').unwrap() + 1)
-                }
-            } else {
-                Err(0)
-            }
-        } else {
-            let mut index = 0;
-            let mut slashes = 0;
-            for ch in s.chars() {
-                if ch == '/' {
-                    slashes += 1
-                }
-                if slashes == 2 {
-                    break
-                }
-                index += 1;
-            }
-            Err(index)
-        }
-    }
-}
+const INTERSECTION_RADIUS: f32 = 0.02;
+const INTERSECTION_COLOR: Color = Color::rgb(1.0, 0.6, 0.2);
+const OUTLINE_COLOR: Color = Color::rgb(0.4, 0.8, 1.0);
+const PEN_RADIUS: f32 = 0.05;
+const PEN_COLOR: Color = Color::rgb(1.0, 0.3, 0.3);
+const FIT_MARGIN: f32 = 1.1;
+/// How far zooming can go out and in, relative to the scale that fits the shape in the window.
+const ZOOM_RANGE: (f32, f32) = (1e-3, 1e4);
+const VERTEX_SEGMENTS: usize = 16;
+const FILL_COLOR: Color = Color::rgb(0.3, 0.3, 0.3);
+/// Where each corner of a stroke lies on its path and its offset from there in pixels, so zooming can move
+/// the corners without tessellating the paths again.
+const ATTRIBUTE_STROKE: MeshVertexAttribute = MeshVertexAttribute::new("Stroke", 988_540_917, VertexFormat::Float32x4);
+/// How far Ctrl+- and Ctrl+= or one line of Alt+scroll move the depth or cut of a variant.
+const VARIANT_STEP: f32 = 0.02;
+/// How far Ctrl+- and Ctrl+= move the pen of a hypotrochoid or epitrochoid.
+const PEN_STEP: f32 = 0.1;
+/// Longest step a morph takes in one frame, so the first frame after an idle spell doesn't skip to the end.
+const MAX_FRAME_TIME: f32 = 0.1;
 
 #[derive(Clone)]
 struct Data {
     material: Handle<ColorMaterial>,
-    vertex: Mesh2dHandle,
+    vertices: Handle<Mesh>,
+    lines: Handle<Mesh>,
+    fill_mesh: Handle<Mesh>,
+    intersections: Handle<Mesh>,
+    outline: Handle<Mesh>,
+    pen: Handle<Mesh>,
+    /// The last polygon shown, or the one a derived polygon is derived from. It stays while a tiling, chord
+    /// diagram or curve is shown for the keys that step it.
     polygon: Polygon,
+    symbol: Symbol,
+    /// Drawn instead of the polygon it is built on while set; only kept while a plain polygon is shown.
+    variant: Option<Variant>,
+    /// Whether the polygon is drawn over the curve.
+    overlay: bool,
+    fill: Option<FillMode>,
+    show_intersections: bool,
+    show_outline: bool,
     scale: f32
 }
+impl Data {
+    fn set_symbol(&mut self, symbol: Symbol) {
+        match &symbol {
+            Symbol::Polygon(polygon) => self.polygon = polygon.clone(),
+            Symbol::Derived(derived) => self.polygon = derived.polygon().clone(),
+            _ => {}
+        }
+        if !matches!(symbol, Symbol::Polygon(_)) {
+            self.variant = None;
+        }
+        self.symbol = symbol;
+    }
+
+    /// Whether the polygon is on screen rather than a tiling, chord diagram, derived polygon, variant or
+    /// curve, which don't fill, morph or trace.
+    fn shows_polygon(&self) -> bool {
+        matches!(self.symbol, Symbol::Polygon(_)) && self.variant.is_none()
+    }
+}
 impl FromWorld for Data {
     fn from_world(world: &mut World) -> Self {
+        let mut images = world.get_resource_mut::<Assets<Image>>().unwrap();
+        let white = images.add(Image::new_fill(
+            Extent3d {
+                width: 1,
+                height: 1,
+                depth_or_array_layers: 1
+            },
+            TextureDimension::D2,
+            &[255, 255, 255, 255],
+            TextureFormat::Rgba8UnormSrgb
+        ));
         let mut materials = world.get_resource_mut::<Assets<ColorMaterial>>().unwrap();
-        let material = materials.add(ColorMaterial::from(Color::rgb(1.0, 1.0, 1.0)));
+        // ColorMaterial only applies vertex colours when it has a texture, so give it a blank one.
+        let material = materials.add(ColorMaterial {
+            color: Color::rgb(1.0, 1.0, 1.0),
+            texture: Some(white)
+        });
         let mut meshes = world.get_resource_mut::<Assets<Mesh>>().unwrap();
-        let vertex = meshes.add(shape::Circle::new(3.0).into()).into();
+        let vertices = meshes.add(Mesh::new(PrimitiveTopology::TriangleList));
+        let lines = meshes.add(Mesh::new(PrimitiveTopology::TriangleList));
+        let fill_mesh = meshes.add(Mesh::new(PrimitiveTopology::TriangleList));
+        let intersections = meshes.add(Mesh::new(PrimitiveTopology::TriangleList));
+        let outline = meshes.add(Mesh::new(PrimitiveTopology::TriangleList));
+        let pen = meshes.add(Mesh::new(PrimitiveTopology::TriangleList));
         Data {
             material,
-            vertex,
-            polygon: Polygon { n: 5, k: 2 },
+            vertices,
+            lines,
+            fill_mesh,
+            intersections,
+            outline,
+            pen,
+            polygon: Polygon::new(5, 2).unwrap(),
+            symbol: Symbol::Polygon(Polygon::new(5, 2).unwrap()),
+            variant: None,
+            overlay: false,
+            fill: None,
+            show_intersections: false,
+            show_outline: false,
             scale: 100.0
         }
     }
 }
 
-fn redraw(mut event: EventReader<Redraw>, data: Res<Data>, meshes: ResMut<Assets<Mesh>>, mut commands: Commands, shapes: Query<Entity, Or<(With<Vertex>, With<Line>)>>) {
-    if event.iter().len() > 0 {
-        for shape in shapes.iter() {
-            commands.entity(shape).despawn();
+/// How the shape is drawn, on screen and in exports.
+#[derive(Clone, PartialEq)]
+struct ShapeStyle {
+    background: Color,
+    /// Compounds still give every component its own hue.
+    edge_color: Color,
+    vertex_color: Color,
+    /// Fraction of the circumradius, so the dots grow with the shape when zooming.
+    vertex_radius: f32,
+    /// Screen pixels at any zoom.
+    stroke_width: f32,
+    join: Join
+}
+impl Default for ShapeStyle {
+    fn default() -> Self {
+        ShapeStyle {
+            background: Color::BLACK,
+            edge_color: Color::WHITE,
+            vertex_color: Color::WHITE,
+            vertex_radius: 0.03,
+            stroke_width: 1.0,
+            join: Join::Round
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Setting {
+    Background,
+    EdgeColor,
+    VertexColor,
+    VertexRadius,
+    StrokeWidth,
+    Join
+}
+impl Setting {
+    const ALL: [Setting; 6] = [
+        Setting::Background,
+        Setting::EdgeColor,
+        Setting::VertexColor,
+        Setting::VertexRadius,
+        Setting::StrokeWidth,
+        Setting::Join
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Setting::Background => "background",
+            Setting::EdgeColor => "edges",
+            Setting::VertexColor => "vertices",
+            Setting::VertexRadius => "vertex radius",
+            Setting::StrokeWidth => "stroke width",
+            Setting::Join => "joins"
+        }
+    }
+}
+
+impl ShapeStyle {
+    fn value(&self, setting: Setting) -> String {
+        let color_name = |color: Color| match PALETTE.iter().find(|(_, c)| *c == color) {
+            Some((name, _)) => name.to_string(),
+            None => format!("{:?}", color)
+        };
+        match setting {
+            Setting::Background => color_name(self.background),
+            Setting::EdgeColor => color_name(self.edge_color),
+            Setting::VertexColor => color_name(self.vertex_color),
+            Setting::VertexRadius => format!("{:.3}", self.vertex_radius),
+            Setting::StrokeWidth => format!("{:.1}px", self.stroke_width),
+            Setting::Join => self.join.name().to_owned()
+        }
+    }
+
+    fn step(&mut self, setting: Setting, forward: bool) {
+        let step_color = |color: &mut Color| {
+            let i = PALETTE.iter().position(|(_, c)| c == color).unwrap_or(0);
+            let i = if forward { (i + 1) % PALETTE.len() } else { (i + PALETTE.len() - 1) % PALETTE.len() };
+            *color = PALETTE[i].1;
+        };
+        let sign = if forward { 1.0 } else { -1.0 };
+        match setting {
+            Setting::Background => step_color(&mut self.background),
+            Setting::EdgeColor => step_color(&mut self.edge_color),
+            Setting::VertexColor => step_color(&mut self.vertex_color),
+            Setting::VertexRadius => self.vertex_radius = (self.vertex_radius + 0.005*sign).clamp(0.0, 0.2),
+            Setting::StrokeWidth => self.stroke_width = (self.stroke_width + 0.5*sign).clamp(0.5, 20.0),
+            Setting::Join => self.join = if forward { self.join.next() } else { self.join.next().next() }
+        }
+    }
+}
+
+/// What undo and redo restore: the shape, what is shown of it, the zoom and the style.
+#[derive(Clone, PartialEq)]
+struct Snapshot {
+    /// Kept apart from `symbol` for when that is a tiling or chord diagram.
+    polygon: Polygon,
+    symbol: Symbol,
+    variant: Option<Variant>,
+    overlay: bool,
+    fill: Option<FillMode>,
+    show_intersections: bool,
+    show_outline: bool,
+    scale: f32,
+    style: ShapeStyle
+}
+impl Snapshot {
+    fn of(data: &Data, style: &ShapeStyle) -> Self {
+        Snapshot {
+            polygon: data.polygon.clone(),
+            symbol: data.symbol.clone(),
+            variant: data.variant,
+            overlay: data.overlay,
+            fill: data.fill,
+            show_intersections: data.show_intersections,
+            show_outline: data.show_outline,
+            scale: data.scale,
+            style: style.clone()
+        }
+    }
+
+    /// Equal apart from the zoom and the depth or cut of the variant, which are folded into the current step
+    /// rather than recorded as one.
+    fn same_view(&self, other: &Snapshot) -> bool {
+        let variant = match (self.variant, other.variant) {
+            (Some(variant), Some(other)) => Some(variant.with_value(other.value())),
+            (variant, _) => variant
+        };
+        Snapshot {
+            variant,
+            scale: other.scale,
+            ..self.clone()
+        } == *other
+    }
+
+    /// Equal apart from what typing a symbol changes: the shape, and the variant it drops.
+    fn same_settings(&self, other: &Snapshot) -> bool {
+        self.same_view(&Snapshot {
+            polygon: self.polygon.clone(),
+            symbol: self.symbol.clone(),
+            variant: other.variant.or(self.variant),
+            ..other.clone()
+        })
+    }
+
+    fn apply(&self, data: &mut Data, style: &mut ShapeStyle) {
+        data.polygon = self.polygon.clone();
+        data.set_symbol(self.symbol.clone());
+        data.variant = self.variant;
+        data.overlay = self.overlay;
+        data.fill = self.fill;
+        data.show_intersections = self.show_intersections;
+        data.show_outline = self.show_outline;
+        data.scale = self.scale;
+        *style = self.style.clone();
+    }
+}
+
+/// Settings and progress of the animated transition between polygons.
+struct Morphing {
+    enabled: bool,
+    /// Seconds.
+    duration: f32,
+    easing: Easing,
+    /// The polygon on screen, or being morphed towards.
+    drawn: Polygon,
+    /// The running transition and the seconds since it started.
+    active: Option<(Morph, f32)>
+}
+impl FromWorld for Morphing {
+    fn from_world(world: &mut World) -> Self {
+        let data = world.get_resource::<Data>().unwrap();
+        Morphing {
+            enabled: false,
+            duration: 1.0,
+            easing: Easing::EaseInOut,
+            drawn: data.polygon.clone(),
+            active: None
+        }
+    }
+}
+
+/// What a sweep varies: `k` of a polygon, or `m` or `n` of a chord diagram.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Parameter {
+    K,
+    M,
+    N
+}
+impl Parameter {
+    fn name(self) -> &'static str {
+        match self {
+            Parameter::K => "k",
+            Parameter::M => "m",
+            Parameter::N => "n"
         }
-        create_shape(commands, meshes, data)
     }
 }
 
-fn create_line_mesh(a: Vec3, b: Vec3) -> Mesh {
-    let vertices = vec![[a.x, a.y, a.z], [b.x, b.y, b.z]];
-    let normal = (a-b).normalize();
-    let normals = vec![[normal.x, normal.y, normal.z], [-normal.x, -normal.y, -normal.z]];
-    let uvs = vec![[0.0, 0.0], [1.0, 1.0]];
-    let indices = mesh::Indices::U16(vec![0, 1]);
+/// Sweeping a parameter through real values: `k` from 1 to `n/2`, where it starts mirroring itself,
+/// `m` from 1 to `n`, where the times table repeats, or `n` from 2 up to the diagram's own.
+struct Sweep {
+    active: bool,
+    playing: bool,
+    looping: bool,
+    /// Steps per second.
+    speed: f32,
+    parameter: Parameter,
+    value: f32
+}
+impl Default for Sweep {
+    fn default() -> Self {
+        Sweep {
+            active: false,
+            playing: true,
+            looping: true,
+            speed: 0.5,
+            parameter: Parameter::K,
+            value: 1.0
+        }
+    }
+}
+impl Sweep {
+    fn range(&self, data: &Data) -> (f32, f32) {
+        match (self.parameter, &data.symbol) {
+            (Parameter::M, Symbol::Chords(rule)) => (1.0, rule.n() as f32),
+            (Parameter::N, Symbol::Chords(rule)) => (2.0, rule.n() as f32),
+            _ => (1.0, (data.polygon.n()/2) as f32)
+        }
+    }
+
+    /// Whether the sweep is of what is on screen, which may have been replaced since it started.
+    fn applies_to(&self, data: &Data) -> bool {
+        match self.parameter {
+            Parameter::K => data.shows_polygon(),
+            Parameter::M | Parameter::N => matches!(data.symbol, Symbol::Chords(_))
+        }
+    }
+}
 
-    let mut mesh = Mesh::new(PrimitiveTopology::LineList);
-    mesh.set_indices(Some(indices));
-    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, vertices);
+/// Revealing the edges one at a time in the order a pen would draw them.
+struct Tracing {
+    active: bool,
+    playing: bool,
+    /// Edges per second.
+    speed: f32,
+    /// Edges drawn so far, the fractional part being how far along the next one the pen is.
+    progress: f32
+}
+impl Default for Tracing {
+    fn default() -> Self {
+        Tracing {
+            active: false,
+            playing: true,
+            speed: 2.0,
+            progress: 0.0
+        }
+    }
+}
+
+/// Starts a morph when the polygon changes with morphing on; anything else is drawn straight away.
+fn redraw(
+        mut event: EventReader<Redraw>,
+        data: Res<Data>,
+        style: Res<ShapeStyle>,
+        mut morphing: ResMut<Morphing>,
+        (sweep, tracing): (Res<Sweep>, Res<Tracing>),
+        mut meshes: ResMut<Assets<Mesh>>,
+        mut request_redraw: EventWriter<RequestRedraw>
+    )
+{
+    if event.iter().len() == 0 {
+        return
+    }
+    if !data.shows_polygon() {
+        // Tilings and chord diagrams are drawn as they are, without the polygon's animations.
+        morphing.active = None;
+        update_shape(&data, &style, &mut meshes);
+        return
+    }
+    let previous = std::mem::replace(&mut morphing.drawn, data.polygon.clone());
+    if morphing.enabled && previous != data.polygon {
+        morphing.active = Some((Morph::new(previous, data.polygon.clone()), 0.0));
+        request_redraw.send(RequestRedraw);
+    }
+    if morphing.active.is_none() && !(sweep.active && sweep.applies_to(&data)) && !tracing.active {
+        update_shape(&data, &style, &mut meshes)
+    }
+}
+
+fn to_vec3(p: Point) -> Vec3 {
+    Vec3::new(p.x, p.y, 0.0)
+}
+
+fn set_geometry(mesh: &mut Mesh, positions: Vec<[f32; 3]>, colors: Vec<[f32; 4]>, indices: Vec<u32>) {
+    let normals = vec![[0.0, 0.0, 1.0]; positions.len()];
+    let uvs = vec![[0.0, 0.0]; positions.len()];
+    mesh.set_indices(Some(mesh::Indices::U32(indices)));
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
     mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
     mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
-    mesh
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    mesh.remove_attribute(ATTRIBUTE_STROKE);
+}
+
+fn vertex_colors(polygon: &Polygon, color: Color) -> Vec<[f32; 4]> {
+    (0..polygon.n()).map(|i| component_color(polygon.component_of(i), polygon.components(), color).as_linear_rgba_f32()).collect()
+}
+
+fn fill_vertex_mesh(mesh: &mut Mesh, vertices: &[Vec3], colors: &[[f32; 4]], radius: f32) {
+    let mut positions = Vec::with_capacity(vertices.len()*(VERTEX_SEGMENTS + 1));
+    let mut vertex_colors = Vec::with_capacity(positions.capacity());
+    let mut indices = Vec::with_capacity(vertices.len()*VERTEX_SEGMENTS*3);
+    for (vertex, &color) in vertices.iter().zip(colors) {
+        let center = positions.len() as u32;
+        positions.push(vertex.to_array());
+        vertex_colors.extend([color; VERTEX_SEGMENTS + 1]);
+        for i in 0..VERTEX_SEGMENTS {
+            let rim = Vec2::from_angle(TAU*(i as f32)/(VERTEX_SEGMENTS as f32))*radius;
+            positions.push((*vertex + rim.extend(0.0)).to_array());
+            let next = (i + 1) % VERTEX_SEGMENTS;
+            indices.extend([center, center + 1 + i as u32, center + 1 + next as u32]);
+        }
+    }
+    set_geometry(mesh, positions, vertex_colors, indices)
+}
+
+/// A polyline to stroke in one colour, closed back to its start if the flag is set.
+type Path = (Vec<Point>, bool, [f32; 4]);
+
+/// Strokes every path `style.stroke_width` pixels wide at the current zoom.
+fn fill_stroke_mesh(mesh: &mut Mesh, paths: &[Path], style: &ShapeStyle, scale: f32) {
+    let mut corners = Vec::new();
+    let mut colors = Vec::new();
+    for (points, closed, color) in paths {
+        for triangle in stroke::stroke_offsets(points, *closed, style.join) {
+            corners.extend(triangle.map(|(p, offset)| [p.x, p.y, offset.x*style.stroke_width, offset.y*style.stroke_width]));
+            colors.extend([*color; 3]);
+        }
+    }
+    let positions = corners.iter().map(|&corner| stroke_corner(corner, scale)).collect::<Vec<[f32; 3]>>();
+    let indices = (0..positions.len() as u32).collect();
+    set_geometry(mesh, positions, colors, indices);
+    mesh.insert_attribute(ATTRIBUTE_STROKE, corners);
+}
+
+fn stroke_corner([x, y, dx, dy]: [f32; 4], scale: f32) -> [f32; 3] {
+    [x + dx/scale, y + dy/scale, 0.0]
+}
+
+/// Moves the corners of a mesh from [`fill_stroke_mesh`] to keep the strokes as wide in pixels at a new zoom.
+fn rescale_stroke_mesh(mesh: &mut Mesh, scale: f32) {
+    if let Some(VertexAttributeValues::Float32x4(corners)) = mesh.attribute(ATTRIBUTE_STROKE) {
+        let positions = corners.iter().map(|&corner| stroke_corner(corner, scale)).collect::<Vec<[f32; 3]>>();
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    }
+}
+
+/// Keeps the strokes on screen as wide in pixels after a zoom, leaving every other mesh as it is.
+fn rescale_strokes(data: &Data, meshes: &mut Assets<Mesh>) {
+    for handle in [&data.lines, &data.outline] {
+        if let Some(mesh) = meshes.get_mut(handle) {
+            rescale_stroke_mesh(mesh, data.scale)
+        }
+    }
+}
+
+fn fill_region_mesh(mesh: &mut Mesh, polygon: &Polygon, mode: Option<FillMode>) {
+    let mut positions = Vec::new();
+    let mut colors = Vec::new();
+    if let Some(mode) = mode {
+        let density = polygon.density();
+        for region in fill::regions(polygon, |j| mode.fills(j)) {
+            let color = match mode {
+                FillMode::Density => {
+                    let [r, g, b] = fill::density_color(region.winding, density);
+                    Color::rgb(r, g, b)
+                },
+                _ => FILL_COLOR
+            };
+            for triangle in region.triangles() {
+                positions.extend(triangle.map(|p| to_vec3(p).to_array()));
+                colors.extend([color.as_linear_rgba_f32(); 3]);
+            }
+        }
+    }
+    let indices = (0..positions.len() as u32).collect();
+    set_geometry(mesh, positions, colors, indices)
 }
 
 #[derive(Component)]
@@ -123,34 +546,486 @@ struct Vertex;
 #[derive(Component)]
 struct Line;
 #[derive(Component)]
-struct InputText;
-
-fn create_shape(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>, data: Res<Data>) {
-    let polygon = &data.polygon;
-    let angle = Vec2::from_angle(TAU/(polygon.n as f32));
-    let line_angle = Vec2::from_angle(TAU*(polygon.k as f32)/(polygon.n as f32));
-    let mut previous = Vec2::new(1.0, 0.0);
-    for _ in 0..polygon.n {
-        commands.spawn_bundle(MaterialMesh2dBundle {
-            mesh: data.vertex.clone(),
-            material: data.material.clone(),
-            transform: Transform::from_translation(previous.extend(0.0)*data.scale),
-            ..default()
-        }).insert(Vertex);
-        commands.spawn_bundle(MaterialMesh2dBundle {
-            mesh: meshes.add(create_line_mesh(previous.extend(0.0)*data.scale, line_angle.rotate(previous).extend(0.0)*data.scale)).into(),
-            material: data.material.clone(),
-            ..default()
-        }).insert(Line);
-        previous = angle.rotate(previous)
+struct Fill;
+#[derive(Component)]
+struct Intersection;
+#[derive(Component)]
+struct Outline;
+#[derive(Component)]
+struct Pen;
+#[derive(Component)]
+struct InputText(TextField);
+#[derive(Component)]
+struct InfoPanel;
+#[derive(Component)]
+struct HistoryPanel;
+#[derive(Component)]
+struct HistoryEntry(usize);
+#[derive(Component)]
+struct SettingsPanel;
+/// Steps a setting forwards or backwards when clicked.
+#[derive(Component)]
+struct SettingButton(Setting, bool);
+#[derive(Component)]
+struct SettingValue(Setting);
+
+fn update_shape(data: &Data, style: &ShapeStyle, meshes: &mut Assets<Mesh>) {
+    match &data.symbol {
+        Symbol::Polygon(_) => {},
+        Symbol::Tiling(tiling) => return draw_patch(tiling.patch(), data, style, meshes),
+        Symbol::Chords(rule) => return draw_chords(rule, data, style, meshes),
+        Symbol::Derived(derived) => return draw_cycles(derived.cycles(), None, data, style, meshes),
+        Symbol::Curve(curve) => {
+            let cycles = if data.overlay { data.polygon.paths() } else { Vec::new() };
+            return draw_cycles(&cycles, Some(curve.points()), data, style, meshes)
+        }
+    }
+    if let Some(variant) = data.variant {
+        return draw_cycles(&variant.cycles(&data.polygon), None, data, style, meshes)
+    }
+    let unit = data.polygon.vertices();
+    let vertices = unit.iter().map(|&p| to_vec3(p)).collect::<Vec<Vec3>>();
+    if let Some(mesh) = meshes.get_mut(&data.vertices) {
+        fill_vertex_mesh(mesh, &vertices, &vertex_colors(&data.polygon, style.vertex_color), style.vertex_radius)
+    }
+    if let Some(mesh) = meshes.get_mut(&data.intersections) {
+        let points = if data.show_intersections { data.polygon.intersections() } else { Vec::new() };
+        let points = points.into_iter().map(to_vec3).collect::<Vec<Vec3>>();
+        let colors = vec![INTERSECTION_COLOR.as_linear_rgba_f32(); points.len()];
+        fill_vertex_mesh(mesh, &points, &colors, INTERSECTION_RADIUS)
+    }
+    if let Some(mesh) = meshes.get_mut(&data.lines) {
+        let colors = vertex_colors(&data.polygon, style.edge_color);
+        let paths = if data.show_outline { Vec::new() } else {
+            data.polygon.cycles().into_iter().map(|cycle| (cycle.iter().map(|&i| unit[i]).collect(), true, colors[cycle[0]])).collect()
+        };
+        fill_stroke_mesh(mesh, &paths, style, data.scale)
+    }
+    if let Some(mesh) = meshes.get_mut(&data.outline) {
+        let paths = if data.show_outline { vec![(data.polygon.outline(), true, OUTLINE_COLOR.as_linear_rgba_f32())] } else { Vec::new() };
+        fill_stroke_mesh(mesh, &paths, style, data.scale)
+    }
+    if let Some(mesh) = meshes.get_mut(&data.fill_mesh) {
+        fill_region_mesh(mesh, &data.polygon, data.fill)
+    }
+    if let Some(mesh) = meshes.get_mut(&data.pen) {
+        fill_vertex_mesh(mesh, &[], &[], PEN_RADIUS)
+    }
+}
+
+/// Draws a tiling with the polygon's vertex and edge style.
+fn draw_patch(patch: &Patch, data: &Data, style: &ShapeStyle, meshes: &mut Assets<Mesh>) {
+    if let Some(mesh) = meshes.get_mut(&data.vertices) {
+        let vertices = patch.vertices.iter().map(|&p| to_vec3(p)).collect::<Vec<Vec3>>();
+        let colors = vec![style.vertex_color.as_linear_rgba_f32(); vertices.len()];
+        fill_vertex_mesh(mesh, &vertices, &colors, style.vertex_radius)
+    }
+    if let Some(mesh) = meshes.get_mut(&data.lines) {
+        let color = style.edge_color.as_linear_rgba_f32();
+        let paths = patch.edges.iter().map(|edge| (edge.clone(), false, color)).collect::<Vec<Path>>();
+        fill_stroke_mesh(mesh, &paths, style, data.scale)
+    }
+    clear_extras(data, meshes)
+}
+
+fn draw_chords(rule: &ChordRule, data: &Data, style: &ShapeStyle, meshes: &mut Assets<Mesh>) {
+    let vertices = rule.vertices();
+    let edges = rule.edges().into_iter().map(|[a, b]| [vertices[a], vertices[b]]).collect::<Vec<[Point; 2]>>();
+    draw_lines(&vertices, &edges, data, style, meshes)
+}
+
+/// Draws the closed paths of a derived polygon or variant like the paths of a polygon, without the extras.
+/// A sampled curve goes underneath in the edge colour and without dots, with the polygon it overlays as `cycles`.
+fn draw_cycles(cycles: &[Vec<Point>], curve: Option<Vec<Point>>, data: &Data, style: &ShapeStyle, meshes: &mut Assets<Mesh>) {
+    let colors = |color: Color| (0..cycles.len()).map(|c| component_color(c, cycles.len(), color).as_linear_rgba_f32()).collect::<Vec<[f32; 4]>>();
+    if let Some(mesh) = meshes.get_mut(&data.vertices) {
+        let vertices = cycles.concat().into_iter().map(to_vec3).collect::<Vec<Vec3>>();
+        let colors = cycles.iter().zip(colors(style.vertex_color)).flat_map(|(cycle, color)| vec![color; cycle.len()]).collect::<Vec<[f32; 4]>>();
+        fill_vertex_mesh(mesh, &vertices, &colors, style.vertex_radius)
+    }
+    if let Some(mesh) = meshes.get_mut(&data.lines) {
+        let curve = curve.map(|points| (points, true, style.edge_color.as_linear_rgba_f32()));
+        let paths = cycles.iter().zip(colors(style.edge_color)).map(|(cycle, color)| (cycle.clone(), true, color));
+        let paths = curve.into_iter().chain(paths).collect::<Vec<Path>>();
+        fill_stroke_mesh(mesh, &paths, style, data.scale)
+    }
+    clear_extras(data, meshes)
+}
+
+/// Plain vertices and straight lines, as drawn by sweeps and chord diagrams.
+fn draw_lines(vertices: &[Point], lines: &[[Point; 2]], data: &Data, style: &ShapeStyle, meshes: &mut Assets<Mesh>) {
+    if let Some(mesh) = meshes.get_mut(&data.vertices) {
+        let vertices = vertices.iter().map(|&p| to_vec3(p)).collect::<Vec<Vec3>>();
+        let colors = vec![style.vertex_color.as_linear_rgba_f32(); vertices.len()];
+        fill_vertex_mesh(mesh, &vertices, &colors, style.vertex_radius)
+    }
+    if let Some(mesh) = meshes.get_mut(&data.lines) {
+        let color = style.edge_color.as_linear_rgba_f32();
+        let paths = lines.iter().map(|line| (line.to_vec(), false, color)).collect::<Vec<Path>>();
+        fill_stroke_mesh(mesh, &paths, style, data.scale)
+    }
+    clear_extras(data, meshes)
+}
+
+/// Draws a frame of a morph with only vertices and edges; the rest comes back once it ends.
+fn draw_morph(morph: &Morph, t: f32, data: &Data, style: &ShapeStyle, meshes: &mut Assets<Mesh>) {
+    let blend = |color: Color| {
+        let (from, to) = (vertex_colors(morph.from(), color), vertex_colors(morph.to(), color));
+        (0..morph.slots()).map(|i| {
+            let (a, b) = morph.source(i);
+            [0, 1, 2, 3].map(|c| from[a][c] + (to[b][c] - from[a][c])*t)
+        }).collect::<Vec<[f32; 4]>>()
+    };
+    if let Some(mesh) = meshes.get_mut(&data.vertices) {
+        let vertices = morph.vertices(t).into_iter().map(to_vec3).collect::<Vec<Vec3>>();
+        fill_vertex_mesh(mesh, &vertices, &blend(style.vertex_color), style.vertex_radius)
+    }
+    if let Some(mesh) = meshes.get_mut(&data.lines) {
+        let paths = morph.edges(t).into_iter().zip(blend(style.edge_color)).map(|(edge, color)| (edge.to_vec(), false, color)).collect::<Vec<Path>>();
+        fill_stroke_mesh(mesh, &paths, style, data.scale)
+    }
+    clear_extras(data, meshes)
+}
+
+/// Hides the fill, intersections and outline, which only exist for whole polygons, and the pen.
+fn clear_extras(data: &Data, meshes: &mut Assets<Mesh>) {
+    if let Some(mesh) = meshes.get_mut(&data.pen) {
+        fill_vertex_mesh(mesh, &[], &[], PEN_RADIUS)
+    }
+    if let Some(mesh) = meshes.get_mut(&data.intersections) {
+        fill_vertex_mesh(mesh, &[], &[], INTERSECTION_RADIUS)
+    }
+    if let Some(mesh) = meshes.get_mut(&data.outline) {
+        set_geometry(mesh, Vec::new(), Vec::new(), Vec::new())
+    }
+    if let Some(mesh) = meshes.get_mut(&data.fill_mesh) {
+        fill_region_mesh(mesh, &data.polygon, None)
+    }
+}
+
+fn animate_morph(
+        time: Res<Time>,
+        data: Res<Data>,
+        style: Res<ShapeStyle>,
+        mut morphing: ResMut<Morphing>,
+        mut meshes: ResMut<Assets<Mesh>>,
+        mut request_redraw: EventWriter<RequestRedraw>
+    )
+{
+    let (duration, easing) = (morphing.duration, morphing.easing);
+    if let Some((morph, elapsed)) = &mut morphing.active {
+        *elapsed += time.delta_seconds().min(MAX_FRAME_TIME);
+        if *elapsed < duration {
+            draw_morph(morph, easing.apply(*elapsed/duration), &data, &style, &mut meshes);
+            // The window only updates on input otherwise.
+            request_redraw.send(RequestRedraw);
+        } else {
+            morphing.active = None;
+            update_shape(&data, &style, &mut meshes)
+        }
+    }
+}
+
+/// Ctrl+M turns morphing on and off, Ctrl+E cycles the easing and Ctrl+[ and Ctrl+] change the duration.
+fn morph_controls(input: Res<Input<KeyCode>>, mut morphing: ResMut<Morphing>, mut redraw_ev: EventWriter<Redraw>) {
+    if !ctrl(&input) {
+        return
+    }
+    if input.just_pressed(KeyCode::M) {
+        morphing.enabled = !morphing.enabled;
+        info!("morphing {}", if morphing.enabled { "on" } else { "off" });
+        if morphing.active.take().is_some() {
+            redraw_ev.send(Redraw)
+        }
+    }
+    if input.just_pressed(KeyCode::E) {
+        morphing.easing = morphing.easing.next();
+        info!("morph easing {:?}", morphing.easing);
+    }
+    if input.just_pressed(KeyCode::LBracket) {
+        morphing.duration = (morphing.duration/1.5).max(0.1);
+        info!("morph duration {:.2}s", morphing.duration);
+    }
+    if input.just_pressed(KeyCode::RBracket) {
+        morphing.duration = (morphing.duration*1.5).min(10.0);
+        info!("morph duration {:.2}s", morphing.duration);
+    }
+}
+
+fn draw_sweep(sweep: &Sweep, data: &Data, style: &ShapeStyle, meshes: &mut Assets<Mesh>) {
+    let (vertices, edges) = match (sweep.parameter, &data.symbol) {
+        (Parameter::M, Symbol::Chords(rule)) => (rule.vertices(), rule.edges_at(rule.n() as f32, Some(sweep.value))),
+        (Parameter::N, Symbol::Chords(rule)) => (chords::vertices_at(sweep.value), rule.edges_at(sweep.value, None)),
+        _ => (data.polygon.vertices(), geometry::fractional_edges(data.polygon.n(), sweep.value))
+    };
+    draw_lines(&vertices, &edges, data, style, meshes)
+}
+
+fn animate_sweep(
+        time: Res<Time>,
+        data: Res<Data>,
+        style: Res<ShapeStyle>,
+        mut sweep: ResMut<Sweep>,
+        mut meshes: ResMut<Assets<Mesh>>,
+        mut texts: Query<&mut Text, With<InputText>>,
+        mut request_redraw: EventWriter<RequestRedraw>
+    )
+{
+    if !sweep.active || !sweep.applies_to(&data) {
+        if sweep.is_changed() {
+            for mut text in &mut texts {
+                text.sections[LABEL].value = symbol_label(&data.symbol);
+            }
+        }
+        return
+    }
+    let (first, last) = sweep.range(&data);
+    if sweep.playing {
+        sweep.value += sweep.speed*time.delta_seconds().min(MAX_FRAME_TIME);
+        if sweep.value >= last {
+            if sweep.looping && last > first {
+                sweep.value = first + (sweep.value - last) % (last - first);
+            } else {
+                sweep.value = last;
+                sweep.playing = false;
+            }
+        }
+        request_redraw.send(RequestRedraw);
+    }
+    if sweep.is_changed() || data.is_changed() || style.is_changed() {
+        draw_sweep(&sweep, &data, &style, &mut meshes);
+        for mut text in &mut texts {
+            text.sections[LABEL].value = format!("\n{} = {:.2}", sweep.parameter.name(), sweep.value);
+        }
     }
 }
 
-fn scale(
-        mut scroll_events: EventReader<MouseWheel>, 
+/// Ctrl+K starts and stops sweeping from the current `k`, or `m` of a times table; Ctrl+Shift+K sweeps `n` of
+/// a chord diagram instead. While sweeping, Ctrl+Space pauses, Ctrl+L toggles looping and Ctrl+, and Ctrl+.
+/// change the speed. Stopping settles on the nearest whole value.
+fn sweep_controls(
+        input: Res<Input<KeyCode>>,
+        mut sweep: ResMut<Sweep>,
+        mut tracing: ResMut<Tracing>,
         mut data: ResMut<Data>,
+        mut fields: Query<(&mut InputText, &mut Text)>,
+        mut redraw_ev: EventWriter<Redraw>
+    )
+{
+    // Only plain polygons and chord diagrams have something to sweep.
+    if !ctrl(&input) || !(matches!(data.symbol, Symbol::Chords(_)) || data.shows_polygon()) {
+        return
+    }
+    if input.just_pressed(KeyCode::K) {
+        // A sweep of something no longer on screen is replaced rather than stopped.
+        if sweep.active && sweep.applies_to(&data) {
+            sweep.active = false;
+            let value = sweep.value.round() as usize;
+            let symbol = match (sweep.parameter, &data.symbol) {
+                (Parameter::M, Symbol::Chords(rule)) => ChordRule::times(rule.n(), value as u64).ok().map(Symbol::Chords),
+                (Parameter::N, Symbol::Chords(rule)) => ChordRule::new(value.max(2), rule.rule().clone()).ok().map(Symbol::Chords),
+                _ => Polygon::new(data.polygon.n(), value.clamp(1, data.polygon.n() - 1)).ok().map(Symbol::Polygon)
+            };
+            if let Some(symbol) = symbol {
+                set_input(&mut fields, &symbol);
+                data.set_symbol(symbol);
+            }
+            redraw_ev.send(Redraw)
+        } else {
+            let shift = input.any_pressed([KeyCode::LShift, KeyCode::RShift]);
+            (sweep.parameter, sweep.value) = match &data.symbol {
+                Symbol::Chords(rule) => match rule.multiplier() {
+                    Some(m) if !shift => (Parameter::M, (m % rule.n() as u64) as f32),
+                    _ => (Parameter::N, 2.0)
+                },
+                _ => (Parameter::K, data.polygon.k().min(data.polygon.n() - data.polygon.k()) as f32)
+            };
+            sweep.active = true;
+            sweep.playing = true;
+            tracing.active = false;
+        }
+    }
+    if !sweep.active {
+        return
+    }
+    if input.just_pressed(KeyCode::Space) {
+        sweep.playing = !sweep.playing;
+    }
+    if input.just_pressed(KeyCode::L) {
+        sweep.looping = !sweep.looping;
+        info!("sweep looping {}", if sweep.looping { "on" } else { "off" });
+    }
+    if input.just_pressed(KeyCode::Comma) {
+        sweep.speed = (sweep.speed/1.5).max(0.01);
+        info!("sweep speed {:.2}", sweep.speed);
+    }
+    if input.just_pressed(KeyCode::Period) {
+        sweep.speed = (sweep.speed*1.5).min(20.0);
+        info!("sweep speed {:.2}", sweep.speed);
+    }
+}
+
+/// The edges traced so far in component colours, the one being drawn cut off at the pen.
+fn draw_tracing(progress: f32, data: &Data, style: &ShapeStyle, meshes: &mut Assets<Mesh>) {
+    let unit = data.polygon.vertices();
+    let colors = vertex_colors(&data.polygon, style.edge_color);
+    let mut paths = Vec::new();
+    let mut pen = unit[0];
+    let mut remaining = progress;
+    for cycle in data.polygon.cycles() {
+        let len = cycle.len();
+        if remaining >= len as f32 {
+            paths.push((cycle.iter().map(|&i| unit[i]).collect(), true, colors[cycle[0]]));
+            pen = unit[cycle[0]];
+            remaining -= len as f32;
+            continue
+        }
+        let done = remaining as usize;
+        let mut points = (0..=done).map(|i| unit[cycle[i]]).collect::<Vec<Point>>();
+        let (a, b) = (unit[cycle[done]], unit[cycle[(done + 1) % len]]);
+        let t = remaining.fract();
+        pen = Point::new(a.x + (b.x - a.x)*t, a.y + (b.y - a.y)*t);
+        points.push(pen);
+        paths.push((points, false, colors[cycle[0]]));
+        break
+    }
+    clear_extras(data, meshes);
+    if let Some(mesh) = meshes.get_mut(&data.vertices) {
+        let vertices = unit.iter().map(|&p| to_vec3(p)).collect::<Vec<Vec3>>();
+        fill_vertex_mesh(mesh, &vertices, &vertex_colors(&data.polygon, style.vertex_color), style.vertex_radius)
+    }
+    if let Some(mesh) = meshes.get_mut(&data.lines) {
+        fill_stroke_mesh(mesh, &paths, style, data.scale)
+    }
+    if let Some(mesh) = meshes.get_mut(&data.pen) {
+        fill_vertex_mesh(mesh, &[to_vec3(pen)], &[PEN_COLOR.as_linear_rgba_f32()], PEN_RADIUS)
+    }
+}
+
+fn animate_tracing(
+        time: Res<Time>,
+        data: Res<Data>,
+        style: Res<ShapeStyle>,
+        mut tracing: ResMut<Tracing>,
+        mut meshes: ResMut<Assets<Mesh>>,
+        mut request_redraw: EventWriter<RequestRedraw>
+    )
+{
+    if !tracing.active || !data.shows_polygon() {
+        return
+    }
+    let total = data.polygon.n() as f32;
+    if tracing.playing {
+        tracing.progress = (tracing.progress + tracing.speed*time.delta_seconds().min(MAX_FRAME_TIME)).min(total);
+        if tracing.progress == total {
+            tracing.playing = false;
+        }
+        request_redraw.send(RequestRedraw);
+    }
+    if tracing.is_changed() || data.is_changed() || style.is_changed() {
+        draw_tracing(tracing.progress, &data, &style, &mut meshes)
+    }
+}
+
+/// Ctrl+T starts tracing from the first edge and stops it again; while tracing, Ctrl+Space pauses,
+/// Ctrl+N pauses and jumps to the end of the next edge, and Ctrl+, and Ctrl+. change the speed.
+fn tracing_controls(
+        input: Res<Input<KeyCode>>,
+        data: Res<Data>,
+        mut tracing: ResMut<Tracing>,
+        mut sweep: ResMut<Sweep>,
         mut redraw_ev: EventWriter<Redraw>
-    ) 
+    )
+{
+    if !ctrl(&input) || !data.shows_polygon() {
+        return
+    }
+    if input.just_pressed(KeyCode::T) {
+        tracing.active = !tracing.active;
+        tracing.progress = 0.0;
+        tracing.playing = true;
+        // Both animations draw into the same meshes.
+        sweep.active = false;
+        redraw_ev.send(Redraw)
+    }
+    if !tracing.active {
+        return
+    }
+    if input.just_pressed(KeyCode::Space) {
+        if tracing.progress >= data.polygon.n() as f32 {
+            tracing.progress = 0.0;
+        }
+        tracing.playing = !tracing.playing;
+    }
+    if input.just_pressed(KeyCode::N) {
+        tracing.playing = false;
+        tracing.progress = (tracing.progress.floor() + 1.0).min(data.polygon.n() as f32);
+    }
+    if input.just_pressed(KeyCode::Comma) {
+        tracing.speed = (tracing.speed/1.5).max(0.1);
+        info!("tracing speed {:.2}", tracing.speed);
+    }
+    if input.just_pressed(KeyCode::Period) {
+        tracing.speed = (tracing.speed*1.5).min(100.0);
+        info!("tracing speed {:.2}", tracing.speed);
+    }
+}
+
+fn create_shape(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>, data: Res<Data>, style: Res<ShapeStyle>) {
+    update_shape(&data, &style, &mut meshes);
+    commands.spawn_bundle(MaterialMesh2dBundle {
+        mesh: data.fill_mesh.clone().into(),
+        material: data.material.clone(),
+        ..default()
+    }).insert(Fill);
+    commands.spawn_bundle(MaterialMesh2dBundle {
+        mesh: data.lines.clone().into(),
+        material: data.material.clone(),
+        transform: Transform::from_xyz(0.0, 0.0, 0.1),
+        ..default()
+    }).insert(Line);
+    commands.spawn_bundle(MaterialMesh2dBundle {
+        mesh: data.outline.clone().into(),
+        material: data.material.clone(),
+        transform: Transform::from_xyz(0.0, 0.0, 0.1),
+        ..default()
+    }).insert(Outline);
+    commands.spawn_bundle(MaterialMesh2dBundle {
+        mesh: data.vertices.clone().into(),
+        material: data.material.clone(),
+        transform: Transform::from_xyz(0.0, 0.0, 0.2),
+        ..default()
+    }).insert(Vertex);
+    commands.spawn_bundle(MaterialMesh2dBundle {
+        mesh: data.intersections.clone().into(),
+        material: data.material.clone(),
+        transform: Transform::from_xyz(0.0, 0.0, 0.3),
+        ..default()
+    }).insert(Intersection);
+    commands.spawn_bundle(MaterialMesh2dBundle {
+        mesh: data.pen.clone().into(),
+        material: data.material.clone(),
+        transform: Transform::from_xyz(0.0, 0.0, 0.4),
+        ..default()
+    }).insert(Pen);
+}
+
+fn ctrl(input: &Input<KeyCode>) -> bool {
+    input.any_pressed([KeyCode::LControl, KeyCode::RControl])
+}
+
+fn cursor_to_world(window: &Window, cursor: Vec2, camera: &Transform, data: &Data) -> Vec2 {
+    camera.translation.truncate() + (cursor - Vec2::new(window.width(), window.height())/2.0)/data.scale
+}
+
+fn zoom(
+        input: Res<Input<KeyCode>>,
+        mut scroll_events: EventReader<MouseWheel>,
+        windows: Res<Windows>,
+        mut data: ResMut<Data>,
+        mut cameras: Query<&mut Transform, With<Camera>>,
+        mut meshes: ResMut<Assets<Mesh>>
+    )
 {
     let mut scroll = 0.0;
     for e in scroll_events.iter() {
@@ -159,49 +1034,195 @@ fn scale(
             MouseScrollUnit::Pixel => e.y*0.01
         }
     }
-    if scroll != 0.0 {
-        data.scale *= 1.1f32.powf(scroll);
-        redraw_ev.send(Redraw);
+    // Alt+scroll changes the variant instead.
+    if scroll != 0.0 && !input.any_pressed([KeyCode::LAlt, KeyCode::RAlt]) {
+        let fit = windows.get_primary().map_or(data.scale, fit_scale);
+        let scale = (data.scale*1.1f32.powf(scroll)).clamp(fit*ZOOM_RANGE.0, fit*ZOOM_RANGE.1);
+        let factor = scale/data.scale;
+        if let (Some(window), Ok(mut camera)) = (windows.get_primary(), cameras.get_single_mut()) {
+            if let Some(cursor) = window.cursor_position() {
+                let anchor = cursor_to_world(window, cursor, &camera, &data);
+                let offset = (camera.translation.truncate() - anchor)/factor;
+                camera.translation = (anchor + offset).extend(camera.translation.z);
+            }
+        }
+        data.scale = scale;
+        rescale_strokes(&data, &mut meshes)
+    }
+}
+
+fn pan(
+        buttons: Res<Input<MouseButton>>,
+        windows: Res<Windows>,
+        data: Res<Data>,
+        mut last: Local<Option<Vec2>>,
+        mut cameras: Query<&mut Transform, With<Camera>>
+    )
+{
+    let cursor = windows.get_primary().and_then(|w| w.cursor_position());
+    if buttons.pressed(MouseButton::Left) {
+        if let (Some(previous), Some(cursor), Ok(mut camera)) = (*last, cursor, cameras.get_single_mut()) {
+            camera.translation -= ((cursor - previous)/data.scale).extend(0.0);
+        }
+        *last = cursor;
+    } else {
+        *last = None;
+    }
+}
+
+/// Pixels per unit that fit the circumcircle in the window with a margin.
+fn fit_scale(window: &Window) -> f32 {
+    window.width().min(window.height())/2.0/FIT_MARGIN
+}
+
+fn fit(
+        input: Res<Input<KeyCode>>,
+        windows: Res<Windows>,
+        mut data: ResMut<Data>,
+        mut cameras: Query<&mut Transform, With<Camera>>,
+        mut meshes: ResMut<Assets<Mesh>>
+    )
+{
+    if ctrl(&input) && input.just_pressed(KeyCode::Key0) {
+        if let (Some(window), Ok(mut camera)) = (windows.get_primary(), cameras.get_single_mut()) {
+            data.scale = fit_scale(window);
+            camera.translation = Vec3::new(0.0, 0.0, camera.translation.z);
+            rescale_strokes(&data, &mut meshes)
+        }
+    }
+}
+
+fn cycle_fill(input: Res<Input<KeyCode>>, mut data: ResMut<Data>, mut redraw_ev: EventWriter<Redraw>) {
+    if ctrl(&input) && input.just_pressed(KeyCode::F) {
+        data.fill = match data.fill {
+            None => Some(FillMode::NonZero),
+            Some(FillMode::NonZero) => Some(FillMode::EvenOdd),
+            Some(FillMode::EvenOdd) => Some(FillMode::Density),
+            Some(FillMode::Density) => None
+        };
+        redraw_ev.send(Redraw)
+    }
+}
+
+fn toggle_intersections(input: Res<Input<KeyCode>>, mut data: ResMut<Data>, mut redraw_ev: EventWriter<Redraw>) {
+    if ctrl(&input) && input.just_pressed(KeyCode::I) {
+        data.show_intersections = !data.show_intersections;
+        redraw_ev.send(Redraw)
+    }
+}
+
+fn toggle_outline(input: Res<Input<KeyCode>>, mut data: ResMut<Data>, mut redraw_ev: EventWriter<Redraw>) {
+    if ctrl(&input) && input.just_pressed(KeyCode::O) {
+        data.show_outline = !data.show_outline;
+        redraw_ev.send(Redraw)
+    }
+}
+
+/// Ctrl+U draws the polygon over a curve, or stops drawing it.
+fn toggle_overlay(input: Res<Input<KeyCode>>, mut data: ResMut<Data>, mut redraw_ev: EventWriter<Redraw>) {
+    if ctrl(&input) && input.just_pressed(KeyCode::U) {
+        data.overlay = !data.overlay;
+        redraw_ev.send(Redraw)
+    }
+}
+
+/// Ctrl+G switches from the polygon to its isotoxal star, then its isogonal polygon and back; Ctrl+- and Ctrl+=
+/// or Alt+scroll change the depth or cut.
+fn variant_controls(
+        input: Res<Input<KeyCode>>,
+        mut keys: EventReader<KeyboardInput>,
+        mut scroll_events: EventReader<MouseWheel>,
+        mut data: ResMut<Data>,
+        mut texts: Query<&mut Text, With<InputText>>,
+        mut redraw_ev: EventWriter<Redraw>
+    )
+{
+    let scroll = scroll_events.iter().map(|e| match e.unit {
+        MouseScrollUnit::Line => e.y,
+        MouseScrollUnit::Pixel => e.y*0.01
+    }).sum::<f32>();
+    // Variants are of plain star polygons only.
+    if data.variant.is_none() && !data.shows_polygon() {
+        return
+    }
+    let mut variant = data.variant;
+    if ctrl(&input) && input.just_pressed(KeyCode::G) {
+        variant = match variant {
+            None => Some(Variant::isotoxal(&data.polygon)),
+            Some(Variant::Isotoxal(_)) => Some(Variant::isogonal()),
+            Some(Variant::Isogonal(_)) => None
+        };
+    }
+    if let Some(current) = variant {
+        let mut steps = if input.any_pressed([KeyCode::LAlt, KeyCode::RAlt]) { scroll } else { 0.0 };
+        if ctrl(&input) {
+            for key in keys.iter().filter(|e| e.state == ButtonState::Pressed).filter_map(|e| e.key_code) {
+                match key {
+                    KeyCode::Equals | KeyCode::NumpadAdd => steps += 1.0,
+                    KeyCode::Minus | KeyCode::NumpadSubtract => steps -= 1.0,
+                    _ => {}
+                }
+            }
+        }
+        variant = Some(current.with_value(current.value() + steps*VARIANT_STEP));
+    }
+    if variant != data.variant {
+        data.variant = variant;
+        for mut text in &mut texts {
+            text.sections[LABEL].value = match variant {
+                Some(variant) => format!("\n{}", variant),
+                None => symbol_label(&data.symbol)
+            };
+        }
+        redraw_ev.send(Redraw)
     }
 }
 
+fn sync_camera(data: Res<Data>, mut projections: Query<&mut OrthographicProjection>) {
+    if data.is_changed() {
+        for mut projection in &mut projections {
+            projection.scale = 1.0/data.scale;
+        }
+    }
+}
+
+const CARET_COLOR: Color = Color::rgb(0.6, 0.6, 0.6);
+const SELECTION_COLOR: Color = Color::rgb(1.0, 0.85, 0.3);
+
+// Sections of the input text: error markers, the field split around the caret and selection, then messages below it.
+const MARKERS: usize = 0;
+const BEFORE: usize = 1;
+const CARET_BEFORE: usize = 2;
+const SELECTED: usize = 3;
+const CARET_AFTER: usize = 4;
+const AFTER: usize = 5;
+const ERROR: usize = 6;
+const LABEL: usize = 7;
+
 fn setup_input(mut commands: Commands, assets_server: Res<AssetServer>, data: Res<Data>) {
     commands.spawn_bundle(Camera2dBundle::default());
 
-    commands.spawn_bundle(TextBundle::from_sections([
-        TextSection::new(
-            "\n",
-            TextStyle {
-                font: assets_server.load("consola.ttf"),
-                font_size: 25.0,
-                color: Color::WHITE
-            }
-        ),
-        TextSection::new(
-            '{',
-            TextStyle {
-                font: assets_server.load("consola.ttf"),
-                font_size: 25.0,
-                color: Color::WHITE
-            }
-        ),
-        TextSection::new(
-            data.polygon.to_string(),
-            TextStyle {
-                font: assets_server.load("consola.ttf"),
-                font_size: 25.0,
-                color: Color::WHITE
-            }
-        ),
-        TextSection::new(
-            '}',
-            TextStyle {
-                font: assets_server.load("consola.ttf"),
-                font_size: 25.0,
-                color: Color::WHITE
-            }
-        ),
-    ]).with_text_alignment(TextAlignment::BOTTOM_LEFT).with_style(Style {
+    let style = |font_size, color| TextStyle {
+        font: assets_server.load("consola.ttf"),
+        font_size,
+        color
+    };
+    let field = TextField::new(data.polygon.to_string());
+    let mut text = Text::from_sections([
+        TextSection::new("\n", style(25.0, Color::WHITE)),
+        TextSection::new("", style(25.0, Color::WHITE)),
+        TextSection::new("", style(25.0, CARET_COLOR)),
+        TextSection::new("", style(25.0, SELECTION_COLOR)),
+        TextSection::new("", style(25.0, CARET_COLOR)),
+        TextSection::new("", style(25.0, Color::WHITE)),
+        TextSection::new("", style(18.0, Color::rgb(1.0, 0.4, 0.4))),
+        TextSection::new(compound_label(&data.polygon), style(18.0, Color::GRAY))
+    ]);
+    show_field(&mut text, &field);
+    commands.spawn_bundle(TextBundle {
+        text: text.with_alignment(TextAlignment::BOTTOM_LEFT),
+        ..default()
+    }.with_style(Style {
         align_self: AlignSelf::Center,
         position_type: PositionType::Absolute,
         position: UiRect {
@@ -209,76 +1230,722 @@ fn setup_input(mut commands: Commands, assets_server: Res<AssetServer>, data: Re
             ..default()
         },
         ..default()
-    })).insert(InputText);
+    })).insert(InputText(field));
+}
+
+/// Splits the field's text around the selection, drawing the caret as a bar on whichever side of it the caret is.
+fn show_field(text: &mut Text, field: &TextField) {
+    let (s, selection) = (field.text(), field.selection());
+    let caret_first = field.caret() == selection.start;
+    text.sections[BEFORE].value = s[..selection.start].to_owned();
+    text.sections[CARET_BEFORE].value = if caret_first { "|" } else { "" }.to_owned();
+    text.sections[SELECTED].value = s[selection.clone()].to_owned();
+    text.sections[CARET_AFTER].value = if caret_first { "" } else { "|" }.to_owned();
+    text.sections[AFTER].value = s[selection.end..].to_owned();
+}
+
+/// Clears any error left by the text field and labels a valid symbol.
+fn show_symbol(text: &mut Text, symbol: &Symbol) {
+    text.sections[MARKERS].value = "\n".to_owned();
+    text.sections[ERROR].value = String::new();
+    text.sections[LABEL].value = symbol_label(symbol);
+}
+
+fn symbol_label(symbol: &Symbol) -> String {
+    match symbol {
+        Symbol::Polygon(polygon) => compound_label(polygon),
+        Symbol::Tiling(tiling) => format!("\n{} tiling", tiling.geometry().name()),
+        Symbol::Chords(rule) => match rule.multiplier() {
+            Some(m) => format!("\ntimes table of {} mod {}", m, rule.n()),
+            None => format!("\ni -> {} mod {}", rule.rule(), rule.n())
+        },
+        Symbol::Derived(derived) => {
+            let names = derived.operators().iter().map(|operator| operator.name()).collect::<Vec<&str>>();
+            format!("\n{} {}", names.join(" "), derived.polygon())
+        },
+        Symbol::Curve(curve) => match curve.kind() {
+            CurveKind::Rose => format!("\nrose with n = {}, d = {}", curve.big(), curve.small()),
+            kind => format!("\n{} with R = {}, r = {}, d = {:.2}", kind.name(), curve.big(), curve.small(), curve.distance())
+        }
+    }
+}
+
+fn compound_label(polygon: &Polygon) -> String {
+    match polygon.component() {
+        _ if polygon.components() == 1 => String::new(),
+        (n, 1) => format!("\ncompound of {} {{{}}}", polygon.components(), n),
+        (n, k) => format!("\ncompound of {} {{{}/{}}}", polygon.components(), n, k)
+    }
+}
+
+fn setup_info(mut commands: Commands, assets_server: Res<AssetServer>) {
+    commands.spawn_bundle(TextBundle::from_section(
+        "",
+        TextStyle {
+            font: assets_server.load("consola.ttf"),
+            font_size: 16.0,
+            color: Color::rgb(0.8, 0.8, 0.8)
+        }
+    ).with_style(Style {
+        display: Display::None,
+        position_type: PositionType::Absolute,
+        position: UiRect {
+            top: Val::Px(5.0),
+            left: Val::Px(5.0),
+            ..default()
+        },
+        ..default()
+    })).insert(InfoPanel);
+}
+
+fn info_text(polygon: &Polygon) -> String {
+    let p = Properties::of(polygon);
+    format!(
+        "{}\n\
+        density        {}\n\
+        gcd(n, k)      {}\n\
+        components     {}\n\
+        intersections  {} in {} orbits\n\
+        tip angle      {:.2}°\n\
+        turning angle  {:.2}°\n\
+        edge length    {:.4}\n\
+        perimeter      {:.4}\n\
+        area even-odd  {:.4}\n\
+        area nonzero   {:.4}\n\
+        core inradius  {:.4}",
+        polygon, p.density, p.gcd, p.components, p.intersections, p.intersection_orbits, p.tip_angle.to_degrees(), p.turning_angle.to_degrees(),
+        p.edge_length, p.perimeter, p.area_even_odd, p.area_nonzero, p.core_inradius
+    )
+}
+
+fn tiling_info(tiling: &Tiling) -> String {
+    format!(
+        "{}\n\
+        geometry       {}\n\
+        face           {}\n\
+        vertex figure  {}\n\
+        circumradius   {:.4}\n\
+        inradius       {:.4}\n\
+        faces shown    {}",
+        tiling, tiling.geometry().name(), tiling.p(), tiling.q(), tiling.circumradius(), tiling.inradius(), tiling.patch().faces
+    )
+}
+
+fn chords_info(rule: &ChordRule) -> String {
+    format!(
+        "{}\n\
+        points         {}\n\
+        rule           i -> {} mod {}\n\
+        chords         {}\n\
+        fixed points   {}",
+        rule, rule.n(), rule.rule(), rule.n(), rule.edges().len(), rule.fixed_points()
+    )
+}
+
+fn derived_info(derived: &Derived) -> String {
+    let cycles = derived.cycles();
+    format!(
+        "{}\n\
+        derived from   {}\n\
+        vertices       {}\n\
+        paths          {}",
+        derived, derived.polygon(), cycles.iter().map(Vec::len).sum::<usize>(), cycles.len()
+    )
 }
 
-fn keyboard_input(input: Res<Input<KeyCode>>, mut texts: Query<&mut Text>, mut data: ResMut<Data>, mut redraw_ev: EventWriter<Redraw>) {
-    for mut text in &mut texts {
-        let mut t = text.sections[2].value.clone();
-        if input.just_pressed(KeyCode::Key0) {
-            t.push('0')
+fn curve_info(curve: &Curve) -> String {
+    let parameters = match curve.kind() {
+        CurveKind::Rose => format!("n = {}, d = {}", curve.big(), curve.small()),
+        _ => format!("R = {}, r = {}, d = {:.2}", curve.big(), curve.small(), curve.distance())
+    };
+    format!(
+        "{}\n\
+        curve          {}\n\
+        parameters     {}\n\
+        turns          {}\n\
+        points         {}",
+        curve, curve.kind().name(), parameters, curve.turns(), curve.points().len()
+    )
+}
+
+fn toggle_info(input: Res<Input<KeyCode>>, mut panels: Query<&mut Style, With<InfoPanel>>) {
+    if input.just_pressed(KeyCode::Tab) {
+        for mut style in &mut panels {
+            style.display = match style.display {
+                Display::None => Display::Flex,
+                Display::Flex => Display::None
+            };
         }
-        if input.just_pressed(KeyCode::Key1) {
-            t.push('1')
+    }
+}
+
+/// Only while the panel is open and when the shape itself changes, not the zoom, as measuring a large
+/// polygon takes a while.
+fn update_info(
+        data: Res<Data>,
+        mut panels: Query<(&mut Text, &Style, ChangeTrackers<Style>), With<InfoPanel>>,
+        mut shown: Local<Option<(Symbol, Option<Variant>)>>
+    )
+{
+    for (mut text, style, style_tracker) in &mut panels {
+        if style.display == Display::None || !(data.is_changed() || style_tracker.is_changed()) {
+            continue
         }
-        if input.just_pressed(KeyCode::Key2) {
-            t.push('2')
+        if matches!(&*shown, Some((symbol, variant)) if *symbol == data.symbol && *variant == data.variant) {
+            continue
         }
-        if input.just_pressed(KeyCode::Key3) {
-            t.push('3')
+        *shown = Some((data.symbol.clone(), data.variant));
+        text.sections[0].value = match &data.symbol {
+            Symbol::Polygon(polygon) => match data.variant {
+                Some(variant) => format!("{}\nvariant        {}", info_text(polygon), variant),
+                None => info_text(polygon)
+            },
+            Symbol::Tiling(tiling) => tiling_info(tiling),
+            Symbol::Chords(rule) => chords_info(rule),
+            Symbol::Derived(derived) => derived_info(derived),
+            Symbol::Curve(curve) => curve_info(curve)
+        };
+    }
+}
+
+fn keyboard_input(
+        input: Res<Input<KeyCode>>,
+        mut keys: EventReader<KeyboardInput>,
+        mut chars: EventReader<ReceivedCharacter>,
+        mut fields: Query<(&mut InputText, &mut Text)>,
+        mut data: ResMut<Data>,
+        mut redraw_ev: EventWriter<Redraw>
+    )
+{
+    let shift = input.any_pressed([KeyCode::LShift, KeyCode::RShift]);
+    // AltGr arrives as Ctrl+Alt on Windows and still types characters.
+    let shortcut = ctrl(&input) && !input.any_pressed([KeyCode::LAlt, KeyCode::RAlt]);
+    if shortcut && input.just_pressed(KeyCode::V) {
+        clipboard::request_paste()
+    }
+    let pasted = clipboard::take_pasted();
+    // Key repeats arrive as further presses, so holding Backspace or an arrow keeps going.
+    let pressed = keys.iter()
+        .filter(|e| e.state == ButtonState::Pressed)
+        .filter_map(|e| e.key_code)
+        .collect::<Vec<KeyCode>>();
+    let typed = chars.iter().map(|e| e.char).filter(|c| !c.is_control()).collect::<String>();
+    for (mut field, mut text) in &mut fields {
+        let field = &mut field.0;
+        let mut edited = false;
+        if !shortcut && !typed.is_empty() {
+            field.insert(&typed);
+            edited = true;
         }
-        if input.just_pressed(KeyCode::Key4) {
-            t.push('4')
+        if let Some(pasted) = &pasted {
+            field.insert(pasted.lines().next().unwrap_or_default().trim());
+            edited = true;
         }
-        if input.just_pressed(KeyCode::Key5) {
-            t.push('5')
+        for &key in &pressed {
+            match key {
+                KeyCode::Left if !shortcut => field.left(shift),
+                KeyCode::Right if !shortcut => field.right(shift),
+                KeyCode::Home => field.home(shift),
+                KeyCode::End => field.end(shift),
+                KeyCode::A if shortcut => field.select_all(),
+                KeyCode::Back => {
+                    field.backspace();
+                    edited = true;
+                },
+                KeyCode::Delete => {
+                    field.delete();
+                    edited = true;
+                },
+                KeyCode::Return | KeyCode::NumpadEnter if field.text().parse::<Symbol>().is_ok() => {
+                    field.commit();
+                    // Records what was typed as one step in the history.
+                    data.set_changed();
+                },
+                KeyCode::Escape => {
+                    field.revert();
+                    edited = true;
+                },
+                _ => {}
+            }
         }
-        if input.just_pressed(KeyCode::Key6) {
-            t.push('6')
+        show_field(&mut text, field);
+        if !edited {
+            continue
         }
-        if input.just_pressed(KeyCode::Key7) {
-            t.push('7')
+        match field.text().parse::<Symbol>() {
+            Ok(symbol) => {
+                show_symbol(&mut text, &symbol);
+                if data.symbol != symbol {
+                    data.set_symbol(symbol);
+                    redraw_ev.send(Redraw)
+                }
+            },
+            Err(e) => {
+                // The caret bar takes up a column of its own in front of everything after it.
+                let column = |i: usize| field.text()[..i].chars().count() + usize::from(i >= field.caret());
+                let width = (column(e.span.end) - column(e.span.start)).max(1);
+                text.sections[MARKERS].value = " ".repeat(column(e.span.start)) + &"v".repeat(width) + "\n";
+                text.sections[ERROR].value = format!("\n{}", e);
+                text.sections[LABEL].value = String::new();
+            }
+        }
+    }
+}
+
+fn setup_settings(mut commands: Commands, assets_server: Res<AssetServer>, style: Res<ShapeStyle>) {
+    let text_style = TextStyle {
+        font: assets_server.load("consola.ttf"),
+        font_size: 16.0,
+        color: Color::rgb(0.8, 0.8, 0.8)
+    };
+    let button = |parent: &mut ChildBuilder, setting: Setting, forward: bool| {
+        parent.spawn_bundle(ButtonBundle {
+            color: Color::NONE.into(),
+            ..default()
+        }).insert(SettingButton(setting, forward)).with_children(|button| {
+            button.spawn_bundle(TextBundle::from_section(if forward { " >" } else { "< " }, text_style.clone()));
+        });
+    };
+    commands.spawn_bundle(NodeBundle {
+        style: Style {
+            display: Display::None,
+            flex_direction: FlexDirection::ColumnReverse,
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                bottom: Val::Px(60.0),
+                right: Val::Px(5.0),
+                ..default()
+            },
+            padding: UiRect::all(Val::Px(5.0)),
+            ..default()
+        },
+        color: Color::rgba(0.0, 0.0, 0.0, 0.7).into(),
+        ..default()
+    }).insert(SettingsPanel).with_children(|panel| {
+        for setting in Setting::ALL {
+            panel.spawn_bundle(NodeBundle {
+                color: Color::NONE.into(),
+                ..default()
+            }).with_children(|row| {
+                row.spawn_bundle(TextBundle::from_section(format!("{:<14}", setting.label()), text_style.clone()));
+                button(row, setting, false);
+                row.spawn_bundle(TextBundle::from_section(format!("{:^8}", style.value(setting)), text_style.clone()))
+                    .insert(SettingValue(setting));
+                button(row, setting, true);
+            });
+        }
+    });
+}
+
+/// F2 shows and hides the settings.
+fn toggle_settings(input: Res<Input<KeyCode>>, mut panels: Query<&mut Style, With<SettingsPanel>>) {
+    if input.just_pressed(KeyCode::F2) {
+        for mut style in &mut panels {
+            style.display = match style.display {
+                Display::None => Display::Flex,
+                Display::Flex => Display::None
+            };
+        }
+    }
+}
+
+fn click_settings(buttons: Query<(&Interaction, &SettingButton), Changed<Interaction>>, mut style: ResMut<ShapeStyle>) {
+    for (interaction, button) in &buttons {
+        if *interaction == Interaction::Clicked {
+            style.step(button.0, button.1)
+        }
+    }
+}
+
+fn apply_style(
+        style: Res<ShapeStyle>,
+        mut clear_color: ResMut<ClearColor>,
+        mut values: Query<(&mut Text, &SettingValue)>,
+        mut redraw_ev: EventWriter<Redraw>
+    )
+{
+    if style.is_changed() {
+        clear_color.0 = style.background;
+        for (mut text, value) in &mut values {
+            text.sections[0].value = format!("{:^8}", style.value(value.0));
+        }
+        redraw_ev.send(Redraw)
+    }
+}
+
+/// Every change is a step of its own, except that zooming only updates the current step and the symbols
+/// shown while typing make up one step once Enter commits them.
+fn record_history(
+        data: Res<Data>,
+        style: Res<ShapeStyle>,
+        fields: Query<&InputText>,
+        mut history: ResMut<History<Snapshot>>
+    )
+{
+    if !data.is_changed() && !style.is_changed() {
+        return
+    }
+    let snapshot = Snapshot::of(&data, &style);
+    let typing = fields.iter().any(|field| field.0.is_edited());
+    match history.current() {
+        Some(current) if *current == snapshot => {},
+        Some(current) if current.same_view(&snapshot) => history.replace(snapshot),
+        Some(current) if typing && current.same_settings(&snapshot) => {},
+        _ => history.push(snapshot)
+    }
+}
+
+/// Replaces whatever was being typed with a symbol chosen some other way.
+fn set_input(fields: &mut Query<(&mut InputText, &mut Text)>, symbol: &Symbol) {
+    for (mut field, mut text) in fields {
+        field.0.set(symbol.to_string());
+        show_field(&mut text, &field.0);
+        show_symbol(&mut text, symbol);
+    }
+}
+
+fn restore(
+        snapshot: Snapshot,
+        data: &mut Data,
+        style: &mut ShapeStyle,
+        fields: &mut Query<(&mut InputText, &mut Text)>,
+        redraw_ev: &mut EventWriter<Redraw>
+    )
+{
+    snapshot.apply(data, style);
+    set_input(fields, &snapshot.symbol);
+    redraw_ev.send(Redraw)
+}
+
+fn undo_redo(
+        input: Res<Input<KeyCode>>,
+        mut history: ResMut<History<Snapshot>>,
+        mut data: ResMut<Data>,
+        mut style: ResMut<ShapeStyle>,
+        mut fields: Query<(&mut InputText, &mut Text)>,
+        mut redraw_ev: EventWriter<Redraw>
+    )
+{
+    if ctrl(&input) && input.just_pressed(KeyCode::Z) {
+        let shift = input.any_pressed([KeyCode::LShift, KeyCode::RShift]);
+        let snapshot = if shift { history.redo() } else { history.undo() };
+        if let Some(snapshot) = snapshot.cloned() {
+            restore(snapshot, &mut data, &mut style, &mut fields, &mut redraw_ev)
         }
-        if input.just_pressed(KeyCode::Key8) {
-            t.push('8')
+    }
+}
+
+/// Up/Down step `n` and Ctrl+Left/Right cycle `k`; holding Shift skips compounds. A derived polygon keeps its
+/// operators. On a chord diagram Up/Down step `n` and Ctrl+Left/Right step `m` of a times table. On a curve
+/// Up/Down step `R` and Ctrl+Left/Right step `r`, or `n` and `d` of a rose skipping those with a common
+/// factor, and Ctrl+- and Ctrl+= move the pen.
+fn step_polygon(
+        input: Res<Input<KeyCode>>,
+        mut keys: EventReader<KeyboardInput>,
+        mut data: ResMut<Data>,
+        mut fields: Query<(&mut InputText, &mut Text)>,
+        mut redraw_ev: EventWriter<Redraw>
+    )
+{
+    let pressed = keys.iter().filter(|e| e.state == ButtonState::Pressed).filter_map(|e| e.key_code);
+    if let Symbol::Chords(rule) = &data.symbol {
+        let mut rule = rule.clone();
+        for key in pressed {
+            let stepped = match (key, rule.multiplier()) {
+                (KeyCode::Up, _) => ChordRule::new(rule.n() + 1, rule.rule().clone()),
+                (KeyCode::Down, _) => ChordRule::new((rule.n() - 1).max(2), rule.rule().clone()),
+                (KeyCode::Right, Some(m)) if ctrl(&input) => ChordRule::times(rule.n(), m + 1),
+                (KeyCode::Left, Some(m)) if ctrl(&input) => ChordRule::times(rule.n(), m.saturating_sub(1)),
+                _ => continue
+            };
+            if let Ok(stepped) = stepped {
+                rule = stepped;
+            }
         }
-        if input.just_pressed(KeyCode::Key9) {
-            t.push('9')
+        let symbol = Symbol::Chords(rule);
+        if data.symbol != symbol {
+            set_input(&mut fields, &symbol);
+            data.set_symbol(symbol);
+            redraw_ev.send(Redraw)
         }
-        if input.just_pressed(KeyCode::Slash) {
-            t.push('/')
+        return
+    }
+    if let Symbol::Curve(curve) = &data.symbol {
+        let mut curve = curve.clone();
+        let rose = curve.kind() == CurveKind::Rose;
+        // The next value in either direction down to 1, which for a rose has no factor in common with `other`.
+        let step = |value: u32, other: u32, forward: bool| {
+            let mut value = value;
+            loop {
+                value = if forward { value + 1 } else { value.checked_sub(1).filter(|&v| v > 0)? };
+                if !rose || geometry::gcd(value as usize, other as usize) == 1 {
+                    return Some(value)
+                }
+            }
+        };
+        for key in pressed {
+            let (big, small, distance) = (curve.big(), curve.small(), curve.distance());
+            let stepped = match key {
+                KeyCode::Up => step(big, small, true).map(|big| (big, small, distance)),
+                KeyCode::Down => step(big, small, false).map(|big| (big, small, distance)),
+                KeyCode::Right if ctrl(&input) => step(small, big, true).map(|small| (big, small, distance)),
+                KeyCode::Left if ctrl(&input) => step(small, big, false).map(|small| (big, small, distance)),
+                KeyCode::Equals | KeyCode::NumpadAdd if ctrl(&input) && !rose => Some((big, small, distance + PEN_STEP)),
+                KeyCode::Minus | KeyCode::NumpadSubtract if ctrl(&input) && !rose => Some((big, small, (distance - PEN_STEP).max(0.0))),
+                _ => continue
+            };
+            if let Some(Ok(stepped)) = stepped.map(|(big, small, distance)| Curve::new(curve.kind(), big, small, distance)) {
+                curve = stepped;
+            }
         }
-        if input.just_pressed(KeyCode::Back) {
-            t.pop();
+        let symbol = Symbol::Curve(curve);
+        if data.symbol != symbol {
+            set_input(&mut fields, &symbol);
+            data.set_symbol(symbol);
+            redraw_ev.send(Redraw)
         }
-        text.sections[2].value = t;
-        match text.sections[2].value.parse::<Polygon>() {
-            Ok(p) => {
-                text.sections[0].value = "\n".to_owned();
-                data.polygon = p;
-                redraw_ev.send(Redraw)
+        return
+    }
+    let coprime = input.any_pressed([KeyCode::LShift, KeyCode::RShift]);
+    let mut polygon = data.polygon.clone();
+    for key in pressed {
+        polygon = match key {
+            KeyCode::Up => polygon.step_n(true, coprime),
+            KeyCode::Down => polygon.step_n(false, coprime),
+            KeyCode::Right if ctrl(&input) => polygon.cycle_k(true, coprime),
+            KeyCode::Left if ctrl(&input) => polygon.cycle_k(false, coprime),
+            _ => continue
+        };
+    }
+    if polygon != data.polygon {
+        let symbol = match &data.symbol {
+            Symbol::Derived(derived) => match derived.with_polygon(polygon) {
+                Ok(derived) => Symbol::Derived(derived),
+                // Such as the dual of a compound of digons, whose edges pass through the centre.
+                Err(_) => return
             },
-            Err(i) => {
-                text.sections[0].value = " ".repeat(i+1) + "v\n";
+            _ => Symbol::Polygon(polygon)
+        };
+        set_input(&mut fields, &symbol);
+        data.set_symbol(symbol);
+        redraw_ev.send(Redraw)
+    }
+}
+
+/// How many of the latest steps the history panel lists.
+const HISTORY_SHOWN: usize = 12;
+
+fn setup_history(mut commands: Commands) {
+    commands.spawn_bundle(NodeBundle {
+        style: Style {
+            display: Display::None,
+            flex_direction: FlexDirection::ColumnReverse,
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                top: Val::Px(5.0),
+                right: Val::Px(5.0),
+                ..default()
+            },
+            ..default()
+        },
+        color: Color::NONE.into(),
+        ..default()
+    }).insert(HistoryPanel);
+}
+
+fn toggle_history(input: Res<Input<KeyCode>>, mut panels: Query<&mut Style, With<HistoryPanel>>) {
+    if ctrl(&input) && input.just_pressed(KeyCode::H) {
+        for mut style in &mut panels {
+            style.display = match style.display {
+                Display::None => Display::Flex,
+                Display::Flex => Display::None
+            };
+        }
+    }
+}
+
+/// Lists the latest steps newest first, with the one on screen highlighted.
+fn update_history(
+        mut commands: Commands,
+        assets_server: Res<AssetServer>,
+        history: Res<History<Snapshot>>,
+        mut shown: Local<(Vec<String>, usize)>,
+        panels: Query<Entity, With<HistoryPanel>>
+    )
+{
+    if !history.is_changed() {
+        return
+    }
+    // Zooming changes the history too, but not the list.
+    let first = history.states().len().saturating_sub(HISTORY_SHOWN);
+    let latest = (history.states()[first..].iter().map(|s| s.symbol.to_string()).collect(), history.position());
+    if *shown == latest {
+        return
+    }
+    *shown = latest;
+    for panel in &panels {
+        commands.entity(panel).despawn_descendants();
+        commands.entity(panel).with_children(|parent| {
+            for (i, snapshot) in history.states().iter().enumerate().skip(first).rev() {
+                let color = if i == history.position() { SELECTION_COLOR } else { Color::rgb(0.8, 0.8, 0.8) };
+                parent.spawn_bundle(ButtonBundle {
+                    style: Style {
+                        justify_content: JustifyContent::FlexEnd,
+                        ..default()
+                    },
+                    color: Color::NONE.into(),
+                    ..default()
+                }).insert(HistoryEntry(i)).with_children(|button| {
+                    button.spawn_bundle(TextBundle::from_section(
+                        snapshot.symbol.to_string(),
+                        TextStyle {
+                            font: assets_server.load("consola.ttf"),
+                            font_size: 16.0,
+                            color
+                        }
+                    ));
+                });
+            }
+        });
+    }
+}
+
+fn pick_history(
+        entries: Query<(&Interaction, &HistoryEntry), Changed<Interaction>>,
+        mut history: ResMut<History<Snapshot>>,
+        mut data: ResMut<Data>,
+        mut style: ResMut<ShapeStyle>,
+        mut fields: Query<(&mut InputText, &mut Text)>,
+        mut redraw_ev: EventWriter<Redraw>
+    )
+{
+    for (interaction, entry) in &entries {
+        if *interaction == Interaction::Clicked {
+            if let Some(snapshot) = history.jump(entry.0).cloned() {
+                restore(snapshot, &mut data, &mut style, &mut fields, &mut redraw_ev)
             }
         }
     }
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+fn export_svg(input: Res<Input<KeyCode>>, data: Res<Data>, style: Res<ShapeStyle>) {
+    if ctrl(&input) && input.just_pressed(KeyCode::S) {
+        let path = data.symbol.file_stem(data.variant) + ".svg";
+        let defaults = SvgOptions::default();
+        let options = SvgOptions {
+            stroke_width: style.stroke_width,
+            join: style.join,
+            color: hex(style.edge_color),
+            background: Some(hex(style.background)),
+            vertex_radius: (style.vertex_radius > 0.0).then_some(style.vertex_radius*defaults.scale),
+            vertex_color: hex(style.vertex_color),
+            fill: data.fill,
+            intersection_radius: data.show_intersections.then_some(2.0),
+            outline: data.show_outline,
+            component_colors: component_colors(data.polygon.components()).into_iter().map(hex).collect(),
+            ..defaults
+        };
+        let result = match data.symbol.lines(data.variant, data.overlay.then_some(&data.polygon)) {
+            Some((vertices, lines, closed)) => svg::write_lines_svg(&path, &vertices, &lines, closed, &options),
+            None => svg::write_svg(&path, &data.polygon, &options)
+        };
+        match result {
+            Ok(()) => info!("wrote {}", path),
+            Err(e) => error!("failed to write {}: {}", path, e)
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn export_png(input: Res<Input<KeyCode>>, windows: Res<Windows>, data: Res<Data>, style: Res<ShapeStyle>) {
+    if ctrl(&input) && input.just_pressed(KeyCode::P) {
+        let mut options = RasterOptions {
+            scale: data.scale,
+            background: rgba8(style.background),
+            color: rgba8(style.edge_color),
+            line_width: style.stroke_width,
+            join: style.join,
+            vertex_color: rgba8(style.vertex_color),
+            fill: data.fill,
+            intersection_radius: data.show_intersections.then_some(2.0),
+            outline: data.show_outline,
+            component_colors: component_colors(data.polygon.components()).into_iter().map(rgba8).collect(),
+            ..default()
+        };
+        if let Some(window) = windows.get_primary() {
+            options.width = window.physical_width();
+            options.height = window.physical_height();
+            options.scale *= window.scale_factor() as f32;
+            options.line_width *= window.scale_factor() as f32;
+        }
+        options.vertex_radius = (style.vertex_radius > 0.0).then_some(style.vertex_radius*options.scale);
+        let path = data.symbol.file_stem(data.variant) + ".png";
+        let result = match data.symbol.lines(data.variant, data.overlay.then_some(&data.polygon)) {
+            Some((vertices, lines, closed)) => raster::write_lines_png(&path, &vertices, &lines, closed, &options),
+            None => raster::write_png(&path, &data.polygon, &options)
+        };
+        match result {
+            Ok(()) => info!("wrote {}", path),
+            Err(e) => error!("failed to write {}: {}", path, e)
+        }
+    }
+}
+
 pub struct Shaper2D;
 impl Plugin for Shaper2D {
     fn build(&self, app: &mut App) {
         app.init_resource::<Data>()
+            .init_resource::<ShapeStyle>()
+            .init_resource::<History<Snapshot>>()
+            .init_resource::<Morphing>()
+            .init_resource::<Sweep>()
+            .init_resource::<Tracing>()
             .add_event::<Redraw>()
             .add_startup_system(setup_input)
+            .add_startup_system(setup_info)
+            .add_startup_system(setup_history)
+            .add_startup_system(setup_settings)
             .add_startup_system(create_shape)
             .add_system(keyboard_input)
-            .add_system(scale)
+            .add_system(zoom)
+            .add_system(pan)
+            .add_system(fit)
+            .add_system(cycle_fill)
+            .add_system(toggle_intersections)
+            .add_system(toggle_outline)
+            .add_system(variant_controls)
+            .add_system(toggle_overlay)
+            .add_system(toggle_info)
+            .add_system(update_info)
+            .add_system(toggle_settings)
+            .add_system(click_settings)
+            .add_system(apply_style)
+            .add_system(record_history)
+            .add_system(undo_redo)
+            .add_system(step_polygon)
+            .add_system(toggle_history)
+            .add_system(update_history)
+            .add_system(pick_history)
+            .add_system(sync_camera)
+            .add_system(morph_controls)
+            .add_system(animate_morph)
+            .add_system(sweep_controls)
+            .add_system(animate_sweep)
+            .add_system(tracing_controls)
+            .add_system(animate_tracing)
             .add_system(redraw);
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_system(export_svg)
+            .add_system(export_png);
     }
 }
 
 fn main() {
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(code) = cli::try_run() {
+        std::process::exit(code)
+    }
     App::new()
         .insert_resource(WinitSettings::desktop_app())
         .insert_resource(ClearColor(Color::rgb(0.0, 0.0, 0.0)))
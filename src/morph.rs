@@ -0,0 +1,136 @@
+//! Animated transitions between two star polygons.
+//!
+//! Both polygons are spread over `max(n)` slots, the smaller one by splitting each of its vertices
+//! (and the edge leaving it) into several that start out on top of each other. Every slot then
+//! slides along the circle from its place in one polygon to its place in the other.
+
+use crate::geometry::{Point, Polygon};
+use std::f32::consts::TAU;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut
+}
+impl Easing {
+    /// Maps the elapsed fraction `t` in `0..=1` to how far along the transition is, using cubic curves.
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t*t*t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut => if t < 0.5 { 4.0*t*t*t } else { 1.0 - (2.0 - 2.0*t).powi(3)/2.0 }
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Easing::Linear => Easing::EaseIn,
+            Easing::EaseIn => Easing::EaseOut,
+            Easing::EaseOut => Easing::EaseInOut,
+            Easing::EaseInOut => Easing::Linear
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Morph {
+    from: Polygon,
+    to: Polygon
+}
+impl Morph {
+    pub fn new(from: Polygon, to: Polygon) -> Self {
+        Morph {
+            from,
+            to
+        }
+    }
+
+    pub fn from(&self) -> &Polygon {
+        &self.from
+    }
+
+    pub fn to(&self) -> &Polygon {
+        &self.to
+    }
+
+    pub fn slots(&self) -> usize {
+        self.from.n().max(self.to.n())
+    }
+
+    /// Vertex of `polygon` that slot `i` starts or ends on.
+    fn vertex(&self, polygon: &Polygon, i: usize) -> usize {
+        i*polygon.n()/self.slots()
+    }
+
+    /// The vertices of [`Morph::from`] and [`Morph::to`] that slot `i` travels between.
+    pub fn source(&self, i: usize) -> (usize, usize) {
+        (self.vertex(&self.from, i), self.vertex(&self.to, i))
+    }
+
+    /// Angles are left unwrapped, so an edge end `k` steps ahead keeps turning the same way round.
+    fn angle(&self, i: usize, ahead: bool, t: f32) -> f32 {
+        let angle = |polygon: &Polygon| {
+            let j = self.vertex(polygon, i) + if ahead { polygon.k() } else { 0 };
+            TAU*(j as f32)/(polygon.n() as f32)
+        };
+        let (a, b) = (angle(&self.from), angle(&self.to));
+        a + (b - a)*t
+    }
+
+    fn point(&self, i: usize, ahead: bool, t: f32) -> Point {
+        let (sin, cos) = self.angle(i, ahead, t).sin_cos();
+        Point::new(cos, sin)
+    }
+
+    /// One point per slot at `t` in `0..=1`; `0` gives the vertices of `from` and `1` those of `to`, some repeated.
+    pub fn vertices(&self, t: f32) -> Vec<Point> {
+        (0..self.slots()).map(|i| self.point(i, false, t)).collect()
+    }
+
+    /// One edge per slot, running from its vertex to the point `k` vertices ahead.
+    pub fn edges(&self, t: f32) -> Vec<[Point; 2]> {
+        (0..self.slots()).map(|i| [self.point(i, false, t), self.point(i, true, t)]).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn morph(from: &str, to: &str) -> Morph {
+        Morph::new(from.parse().unwrap(), to.parse().unwrap())
+    }
+
+    fn close(a: &[Point], b: &[Point]) -> bool {
+        a.len() == b.len() && a.iter().zip(b).all(|(p, q)| (p.x - q.x).hypot(p.y - q.y) < 1e-4)
+    }
+
+    #[test]
+    fn slots() {
+        let forward = morph("5/2", "10/3");
+        assert_eq!(forward.slots(), 10);
+        assert_eq!((0..4).map(|i| forward.source(i)).collect::<Vec<_>>(), [(0, 0), (0, 1), (1, 2), (1, 3)]);
+        assert_eq!(forward.source(9), (4, 9));
+        let back = morph("10/3", "5/2");
+        assert_eq!(back.source(3), (3, 1));
+        let same = morph("7/2", "7/3");
+        assert!((0..7).all(|i| same.source(i) == (i, i)));
+    }
+
+    #[test]
+    fn ends() {
+        let morph = morph("5/2", "10/3");
+        let (from, to) = (morph.from().vertices(), morph.to().vertices());
+        let doubled = (0..10).map(|i| from[i/2]).collect::<Vec<Point>>();
+        assert!(close(&morph.vertices(0.0), &doubled));
+        assert!(close(&morph.vertices(1.0), &to));
+        let ahead = morph.edges(1.0).iter().map(|edge| edge[1]).collect::<Vec<Point>>();
+        assert!(close(&ahead, &(0..10).map(|i| to[(i + 3) % 10]).collect::<Vec<Point>>()));
+        let ahead = morph.edges(0.0).iter().map(|edge| edge[1]).collect::<Vec<Point>>();
+        assert!(close(&ahead, &(0..10).map(|i| from[(i/2 + 2) % 5]).collect::<Vec<Point>>()));
+    }
+}
@@ -0,0 +1,241 @@
+//! Conway-style operators on star polygons: `t{5/2}` truncates, `r` rectifies, `d` takes the dual and `s`
+//! alternates. They compose right to left, so `tr{8/3}` truncates the rectified `{8/3}`.
+//!
+//! The operators work on the closed paths of the figure rather than on `{n/k}`, so a result whose vertices
+//! fall on top of each other, such as `t{5/2}` going twice round a pentagon, is kept as it is. Every result
+//! is scaled back to a circumradius of 1.
+
+use crate::{
+    geometry::{Point, Polygon},
+    symbol::{SymbolError, SymbolErrorKind}
+};
+use std::{
+    fmt,
+    str::FromStr
+};
+
+/// Most points a derived figure can have; each `t` doubles them.
+pub const MAX_POINTS: usize = 10_000;
+
+/// Edges shorter than this, or lines closer to the centre, have no direction or pole for [`Operator::Dual`].
+const EPSILON: f32 = 1e-5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operator {
+    /// Cuts every corner so that, on a regular figure, the new edges are as long as what is left of the old ones.
+    Truncate,
+    /// Joins the midpoints of the edges.
+    Rectify,
+    /// The polar reciprocal: one vertex per edge, at the pole of its line with respect to the unit circle.
+    Dual,
+    /// Keeps every other vertex, which needs an even number of them on every path.
+    Alternate
+}
+impl Operator {
+    pub fn symbol(self) -> char {
+        match self {
+            Operator::Truncate => 't',
+            Operator::Rectify => 'r',
+            Operator::Dual => 'd',
+            Operator::Alternate => 's'
+        }
+    }
+
+    pub fn from_symbol(ch: char) -> Option<Self> {
+        match ch {
+            't' => Some(Operator::Truncate),
+            'r' => Some(Operator::Rectify),
+            'd' => Some(Operator::Dual),
+            's' => Some(Operator::Alternate),
+            _ => None
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Operator::Truncate => "truncated",
+            Operator::Rectify => "rectified",
+            Operator::Dual => "dual",
+            Operator::Alternate => "alternated"
+        }
+    }
+
+    /// Applies the operator to closed paths and scales the result back to the unit circle.
+    pub fn apply(self, cycles: &[Vec<Point>]) -> Result<Vec<Vec<Point>>, OperatorError> {
+        if self == Operator::Truncate && 2*cycles.iter().map(Vec::len).sum::<usize>() > MAX_POINTS {
+            return Err(OperatorError::TooManyPoints)
+        }
+        let cycles = cycles.iter().map(|cycle| match self {
+            Operator::Truncate => Ok(truncate(cycle)),
+            Operator::Rectify => Ok(edges(cycle).map(|(a, b)| lerp(a, b, 0.5)).collect()),
+            Operator::Dual => edges(cycle).map(|(a, b)| pole(a, b).ok_or(OperatorError::NoDual)).collect(),
+            Operator::Alternate if cycle.len() % 2 == 0 => Ok(cycle.iter().step_by(2).copied().collect()),
+            Operator::Alternate => Err(OperatorError::OddAlternation)
+        }).collect::<Result<Vec<Vec<Point>>, OperatorError>>()?;
+        let radius = cycles.iter().flatten().map(|p| p.x.hypot(p.y)).fold(0.0, f32::max);
+        if radius < EPSILON {
+            return Err(OperatorError::Collapsed)
+        }
+        Ok(cycles.into_iter().map(|cycle| cycle.into_iter().map(|p| p.scale(1.0/radius)).collect()).collect())
+    }
+}
+
+fn lerp(a: Point, b: Point, t: f32) -> Point {
+    Point::new(a.x + (b.x - a.x)*t, a.y + (b.y - a.y)*t)
+}
+
+/// Every edge of a closed path, from each point to the next.
+fn edges(cycle: &[Point]) -> impl Iterator<Item = (Point, Point)> + '_ {
+    cycle.iter().enumerate().map(|(i, &a)| (a, cycle[(i + 1) % cycle.len()]))
+}
+
+/// Two points per edge. At a corner turning by `τ`, `1/(2 + 2 cos(τ/2))` of each edge is cut off, which
+/// turns `{n/k}` into `{2n/k}`.
+fn truncate(cycle: &[Point]) -> Vec<Point> {
+    let len = cycle.len();
+    let cut = |i: usize| {
+        let (previous, p, next) = (cycle[(i + len - 1) % len], cycle[i], cycle[(i + 1) % len]);
+        let (ax, ay, bx, by) = (p.x - previous.x, p.y - previous.y, next.x - p.x, next.y - p.y);
+        let turn = (ax*by - ay*bx).atan2(ax*bx + ay*by);
+        1.0/(2.0 + 2.0*(turn/2.0).cos())
+    };
+    (0..len).flat_map(|i| {
+        let (a, b) = (cycle[i], cycle[(i + 1) % len]);
+        [lerp(a, b, cut(i)), lerp(b, a, cut((i + 1) % len))]
+    }).collect()
+}
+
+/// The pole of the line through `a` and `b`, unless they coincide or the line passes through the centre.
+fn pole(a: Point, b: Point) -> Option<Point> {
+    let length = (b.x - a.x).hypot(b.y - a.y);
+    if length < EPSILON {
+        return None
+    }
+    let normal = Point::new((b.y - a.y)/length, (a.x - b.x)/length);
+    let distance = normal.x*a.x + normal.y*a.y;
+    (distance.abs() >= EPSILON).then(|| normal.scale(1.0/distance))
+}
+
+/// A star polygon with operators applied, innermost last, as in `tr{8/3}`.
+///
+/// Pairs of `d` cancel out, since the dual of the dual is the figure itself.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Derived {
+    operators: Vec<Operator>,
+    polygon: Polygon,
+    cycles: Vec<Vec<Point>>
+}
+impl Derived {
+    pub fn new(operators: Vec<Operator>, polygon: Polygon) -> Result<Self, OperatorError> {
+        let operators = cancel_duals(&operators).into_iter().map(|i| operators[i]).collect::<Vec<Operator>>();
+        let cycles = derive(&operators, &polygon).map_err(|(_, e)| e)?;
+        Ok(Derived {
+            operators,
+            polygon,
+            cycles
+        })
+    }
+
+    pub fn operators(&self) -> &[Operator] {
+        &self.operators
+    }
+
+    pub fn polygon(&self) -> &Polygon {
+        &self.polygon
+    }
+
+    /// The same operators on another polygon.
+    pub fn with_polygon(&self, polygon: Polygon) -> Result<Self, OperatorError> {
+        Derived::new(self.operators.clone(), polygon)
+    }
+
+    /// The closed paths of the figure, one per component of the polygon unless an operator merged them.
+    pub fn cycles(&self) -> &[Vec<Point>] {
+        &self.cycles
+    }
+}
+
+/// Indices of the operators left once adjacent pairs of duals are removed.
+fn cancel_duals(operators: &[Operator]) -> Vec<usize> {
+    let mut kept: Vec<usize> = Vec::new();
+    for (i, &operator) in operators.iter().enumerate() {
+        match kept.last() {
+            Some(&last) if operator == Operator::Dual && operators[last] == Operator::Dual => {
+                kept.pop();
+            },
+            _ => kept.push(i)
+        }
+    }
+    kept
+}
+
+/// Applies the operators from the innermost out, returning which one failed.
+fn derive(operators: &[Operator], polygon: &Polygon) -> Result<Vec<Vec<Point>>, (usize, OperatorError)> {
+    operators.iter().enumerate().rev().try_fold(polygon.paths(), |cycles, (i, operator)| operator.apply(&cycles).map_err(|e| (i, e)))
+}
+
+/// Prints the operators that are left, followed by the polygon as [`Polygon`] prints it.
+impl fmt::Display for Derived {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for operator in &self.operators {
+            write!(f, "{}", operator.symbol())?;
+        }
+        self.polygon.fmt(f)
+    }
+}
+/// Accepts operator letters followed by anything [`Polygon`] accepts, such as `t{5/2}`, `dr8/3` or `s2{4}`.
+impl FromStr for Derived {
+    type Err = SymbolError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let start = s.find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(s.len());
+        let operators = s[..start].char_indices().map(|(i, ch)| {
+            Operator::from_symbol(ch).ok_or_else(|| SymbolError::new(i..i + ch.len_utf8(), SymbolErrorKind::UnknownOperator(ch)))
+        }).collect::<Result<Vec<Operator>, SymbolError>>()?;
+        let polygon = s[start..].parse::<Polygon>().map_err(|e| SymbolError::new(e.span.start + start..e.span.end + start, e.kind.into()))?;
+        let kept = cancel_duals(&operators);
+        let operators = kept.iter().map(|&i| operators[i]).collect::<Vec<Operator>>();
+        let cycles = derive(&operators, &polygon).map_err(|(i, e)| SymbolError::new(kept[i]..kept[i] + 1, e.into()))?;
+        Ok(Derived {
+            operators,
+            polygon,
+            cycles
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OperatorError {
+    NoDual,
+    OddAlternation,
+    Collapsed,
+    TooManyPoints
+}
+impl fmt::Display for OperatorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OperatorError::NoDual => write!(f, "no dual, as an edge is a point or passes through the centre"),
+            OperatorError::OddAlternation => write!(f, "alternation needs an even number of vertices on every path"),
+            OperatorError::Collapsed => write!(f, "the figure collapses to a point"),
+            OperatorError::TooManyPoints => write!(f, "a derived figure can have at most {} points", MAX_POINTS)
+        }
+    }
+}
+impl std::error::Error for OperatorError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn too_many_points() {
+        let points = |s: &str| s.parse::<Derived>().unwrap().cycles().iter().map(Vec::len).sum::<usize>();
+        assert_eq!(points("tttttttttt{5}"), 5120);
+        let e = "ttttttttttt{5}".parse::<Derived>().unwrap_err();
+        assert_eq!((e.span, e.kind), (0..1, SymbolErrorKind::Operator(OperatorError::TooManyPoints)));
+        let e = format!("{}{{5}}", "t".repeat(30)).parse::<Derived>().unwrap_err();
+        assert_eq!((e.span, e.kind), (19..20, SymbolErrorKind::Operator(OperatorError::TooManyPoints)));
+        let polygon = Polygon::new(1000, 1).unwrap();
+        assert_eq!(Derived::new(vec![Operator::Truncate; 4], polygon).unwrap_err(), OperatorError::TooManyPoints);
+    }
+}
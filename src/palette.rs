@@ -0,0 +1,45 @@
+//! Named colours, shared by the settings overlay and the command line, and their forms in image files.
+
+use bevy::render::color::Color;
+
+/// Colours the settings overlay steps through.
+pub const PALETTE: [(&str, Color); 10] = [
+    ("black", Color::BLACK),
+    ("white", Color::WHITE),
+    ("grey", Color::GRAY),
+    ("red", Color::rgb(0.9, 0.2, 0.2)),
+    ("orange", Color::rgb(1.0, 0.6, 0.2)),
+    ("yellow", Color::rgb(1.0, 0.85, 0.3)),
+    ("green", Color::rgb(0.3, 0.8, 0.3)),
+    ("cyan", Color::rgb(0.4, 0.8, 1.0)),
+    ("blue", Color::rgb(0.2, 0.35, 0.9)),
+    ("navy", Color::rgb(0.05, 0.08, 0.2))
+];
+
+/// `color` for a single path, otherwise one evenly spaced hue per component.
+pub fn component_color(c: usize, components: usize, color: Color) -> Color {
+    if components == 1 {
+        color
+    } else {
+        Color::hsl(360.0*(c as f32)/(components as f32), 0.8, 0.6)
+    }
+}
+
+/// The colour of every component of a compound, or none for a single path, as exporters take them.
+pub fn component_colors(components: usize) -> Vec<Color> {
+    match components {
+        1 => Vec::new(),
+        m => (0..m).map(|c| component_color(c, m, Color::NONE)).collect()
+    }
+}
+
+/// sRGB bytes, as image files store colours.
+pub fn rgba8(color: Color) -> [u8; 4] {
+    color.as_rgba_f32().map(|c| (c*255.0).round() as u8)
+}
+
+/// `#rrggbb`, as SVG takes colours.
+pub fn hex(color: Color) -> String {
+    let [r, g, b, _] = rgba8(color);
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
@@ -0,0 +1,93 @@
+use crate::{
+    fill::{self, FillMode},
+    geometry::{gcd, Polygon}
+};
+use std::f32::consts::PI;
+
+/// Measurements of a polygon with unit circumradius. Angles are in radians.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Properties {
+    pub density: usize,
+    pub gcd: usize,
+    pub components: usize,
+    pub intersections: usize,
+    /// Intersections no rotation of the polygon takes to each other.
+    pub intersection_orbits: usize,
+    /// Interior angle at each point of the star.
+    pub tip_angle: f32,
+    /// Angle the path turns through at each vertex.
+    pub turning_angle: f32,
+    pub edge_length: f32,
+    pub perimeter: f32,
+    pub area_even_odd: f32,
+    pub area_nonzero: f32,
+    /// Inradius of the central regular `n`-gon, the region of highest density.
+    pub core_inradius: f32
+}
+impl Properties {
+    pub fn of(polygon: &Polygon) -> Self {
+        let n = polygon.n() as f32;
+        let step = polygon.k().min(polygon.n() - polygon.k()) as f32;
+        let edge_length = 2.0*(PI*step/n).sin();
+        // `k - 1` crossings per rotation, as [`Polygon::intersections`] lists them, without building the list.
+        let (intersections, intersection_orbits) = match polygon.density() {
+            0 => (1, 1),
+            k => (polygon.n()*(k - 1), k - 1)
+        };
+        Properties {
+            density: polygon.density(),
+            gcd: gcd(polygon.n(), polygon.k()),
+            components: polygon.components(),
+            intersections,
+            intersection_orbits,
+            tip_angle: PI - 2.0*PI*step/n,
+            turning_angle: 2.0*PI*step/n,
+            edge_length,
+            perimeter: n*edge_length,
+            area_even_odd: fill::area(polygon, FillMode::EvenOdd),
+            area_nonzero: fill::area(polygon, FillMode::NonZero),
+            core_inradius: (PI*step/n).cos().max(0.0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intersection_counts() {
+        for n in 3..=16 {
+            for k in 1..n {
+                let polygon = Polygon::new(n, k).unwrap();
+                let p = Properties::of(&polygon);
+                assert_eq!(p.intersections, polygon.intersections().len(), "{}", polygon);
+                assert_eq!(p.intersection_orbits, polygon.intersection_orbits().len(), "{}", polygon);
+            }
+        }
+    }
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-3
+    }
+
+    #[test]
+    fn pentagram() {
+        let p = Properties::of(&Polygon::new(5, 2).unwrap());
+        assert_eq!((p.density, p.gcd, p.components), (2, 1, 1));
+        assert_eq!((p.intersections, p.intersection_orbits), (5, 1));
+        assert!(close(p.tip_angle, 36f32.to_radians()), "{}", p.tip_angle.to_degrees());
+        assert!(close(p.turning_angle, 144f32.to_radians()));
+        assert!(close(p.area_nonzero, 1.1226), "{}", p.area_nonzero);
+        assert!(close(p.area_even_odd, 0.7757), "{}", p.area_even_odd);
+    }
+
+    #[test]
+    fn compound_hexagram() {
+        let p = Properties::of(&Polygon::new(6, 2).unwrap());
+        assert_eq!((p.density, p.gcd, p.components), (2, 2, 2));
+        assert!(close(p.tip_angle, 60f32.to_radians()));
+        assert!(close(p.area_nonzero, 3f32.sqrt()), "{}", p.area_nonzero);
+        assert!(close(p.area_even_odd, 3f32.sqrt()/2.0), "{}", p.area_even_odd);
+    }
+}
@@ -0,0 +1,308 @@
+use crate::{
+    fill::{self, FillMode},
+    geometry::{Point, Polygon},
+    stroke::{self, Join}
+};
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path
+};
+
+/// Defaults match what the window shows: white on black, 100 px circumradius and 3 px vertex dots.
+#[derive(Clone, Debug)]
+pub struct RasterOptions {
+    pub width: u32,
+    pub height: u32,
+    /// Circumradius in pixels.
+    pub scale: f32,
+    pub background: [u8; 4],
+    pub color: [u8; 4],
+    pub line_width: f32,
+    pub join: Join,
+    /// Radius of the dot drawn on every vertex in pixels, or `None` for no dots.
+    pub vertex_radius: Option<f32>,
+    pub vertex_color: [u8; 4],
+    /// Radius of the dot drawn where two edges cross in pixels, or `None` for no dots.
+    pub intersection_radius: Option<f32>,
+    pub intersection_color: [u8; 4],
+    /// Draw [`Polygon::outline`] instead of the crossing edges.
+    pub outline: bool,
+    pub fill: Option<FillMode>,
+    /// Used by every fill mode except [`FillMode::Density`], which colours by winding number.
+    pub fill_color: [u8; 4],
+    /// Edge and vertex colour of each component of a compound in turn, repeating if there are fewer, in place of
+    /// `color` and `vertex_color`.
+    /// Single paths and outlines, and everything drawn by [`rasterize_lines`], use those throughout.
+    pub component_colors: Vec<[u8; 4]>
+}
+impl Default for RasterOptions {
+    fn default() -> Self {
+        RasterOptions {
+            width: 500,
+            height: 500,
+            scale: 100.0,
+            background: [0, 0, 0, 255],
+            color: [255, 255, 255, 255],
+            line_width: 1.0,
+            join: Join::Round,
+            vertex_radius: Some(3.0),
+            vertex_color: [255, 255, 255, 255],
+            intersection_radius: None,
+            intersection_color: [255, 153, 51, 255],
+            outline: false,
+            fill: None,
+            fill_color: [77, 77, 77, 255],
+            component_colors: Vec::new()
+        }
+    }
+}
+
+/// An RGBA8 image, rows top to bottom.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Canvas {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>
+}
+impl Canvas {
+    /// Fails rather than panics or aborts if the image is too large to allocate.
+    pub fn new(width: u32, height: u32, background: [u8; 4]) -> io::Result<Self> {
+        let too_large = || io::Error::new(io::ErrorKind::InvalidInput, format!("a {}×{} image is too large", width, height));
+        let bytes = (width as usize).checked_mul(height as usize).and_then(|pixels| pixels.checked_mul(4)).ok_or_else(too_large)?;
+        let mut pixels = Vec::new();
+        pixels.try_reserve_exact(bytes).map_err(|_| too_large())?;
+        pixels.extend(background.iter().cycle().take(bytes));
+        Ok(Canvas {
+            width,
+            height,
+            pixels
+        })
+    }
+
+    /// Source-over blends `color` into the pixel at `(x, y)` with the given coverage.
+    pub fn blend(&mut self, x: i64, y: i64, color: [u8; 4], coverage: f32) {
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 || coverage <= 0.0 {
+            return
+        }
+        let i = ((y as usize)*(self.width as usize) + x as usize)*4;
+        let alpha = coverage.min(1.0)*(color[3] as f32)/255.0;
+        let dst_alpha = self.pixels[i + 3] as f32/255.0;
+        let out_alpha = alpha + dst_alpha*(1.0 - alpha);
+        if out_alpha <= 0.0 {
+            return
+        }
+        for (dst, &src) in self.pixels[i..i + 3].iter_mut().zip(&color) {
+            let mixed = src as f32*alpha + *dst as f32*dst_alpha*(1.0 - alpha);
+            *dst = (mixed/out_alpha).round() as u8;
+        }
+        self.pixels[i + 3] = (out_alpha*255.0).round() as u8;
+    }
+
+    /// Only visits the pixels of its bounding box that are on the canvas, however large the disc.
+    pub fn disc(&mut self, center: Point, radius: f32, color: [u8; 4]) {
+        let reach = radius + 1.0;
+        let clip = |from: f32, to: f32, size: u32| from.floor().max(0.0) as i64..(to.ceil() + 1.0).min(size as f32) as i64;
+        for y in clip(center.y - reach, center.y + reach, self.height) {
+            for x in clip(center.x - reach, center.x + reach, self.width) {
+                let distance = ((x as f32 + 0.5 - center.x).powi(2) + (y as f32 + 0.5 - center.y).powi(2)).sqrt();
+                self.blend(x, y, color, radius + 0.5 - distance);
+            }
+        }
+    }
+
+    /// Fills a simple polygon, antialiased with exact horizontal and 4× vertical coverage.
+    pub fn polygon(&mut self, points: &[Point], color: [u8; 4]) {
+        self.union(&[points], color)
+    }
+
+    /// Fills everything inside any of `polygons` like [`Canvas::polygon`], but without the faint seams
+    /// that filling pieces one by one leaves where they touch.
+    pub fn union(&mut self, polygons: &[&[Point]], color: [u8; 4]) {
+        const SUBSAMPLES: usize = 4;
+        let polygons = polygons.iter().filter(|points| points.len() >= 3).map(|points| {
+            let top = points.iter().map(|p| p.y).fold(f32::INFINITY, f32::min);
+            let bottom = points.iter().map(|p| p.y).fold(f32::NEG_INFINITY, f32::max);
+            (*points, top, bottom)
+        }).collect::<Vec<(&[Point], f32, f32)>>();
+        if polygons.is_empty() {
+            return
+        }
+        let width = self.width as usize;
+        let top = polygons.iter().map(|p| p.1).fold(f32::INFINITY, f32::min).floor().max(0.0) as i64;
+        let bottom = polygons.iter().map(|p| p.2).fold(f32::NEG_INFINITY, f32::max).ceil().min(self.height as f32) as i64;
+        let mut coverage = vec![0.0; width];
+        let mut crossings = Vec::new();
+        let mut spans = Vec::new();
+        for row in top..bottom {
+            coverage.iter_mut().for_each(|c| *c = 0.0);
+            for sub in 0..SUBSAMPLES {
+                let y = row as f32 + (sub as f32 + 0.5)/(SUBSAMPLES as f32);
+                spans.clear();
+                for &(points, _, _) in polygons.iter().filter(|p| p.1 <= y && y <= p.2) {
+                    crossings.clear();
+                    for (i, a) in points.iter().enumerate() {
+                        let b = points[(i + 1) % points.len()];
+                        if (a.y <= y) != (b.y <= y) {
+                            crossings.push(a.x + (y - a.y)*(b.x - a.x)/(b.y - a.y));
+                        }
+                    }
+                    crossings.sort_by(f32::total_cmp);
+                    spans.extend(crossings.chunks_exact(2).map(|span| (span[0], span[1])));
+                }
+                spans.sort_by(|a, b| a.0.total_cmp(&b.0));
+                let mut overlapping = spans.iter().copied();
+                if let Some(mut span) = overlapping.next() {
+                    for (x0, x1) in overlapping {
+                        if x0 <= span.1 {
+                            span.1 = span.1.max(x1);
+                        } else {
+                            add_span(&mut coverage, span, 1.0/(SUBSAMPLES as f32));
+                            span = (x0, x1);
+                        }
+                    }
+                    add_span(&mut coverage, span, 1.0/(SUBSAMPLES as f32));
+                }
+            }
+            for (x, &c) in coverage.iter().enumerate() {
+                self.blend(x as i64, row, color, c);
+            }
+        }
+    }
+
+    pub fn write_png(&self, writer: impl Write) -> io::Result<()> {
+        let mut encoder = png::Encoder::new(writer, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.pixels)?;
+        writer.finish()?;
+        Ok(())
+    }
+
+    pub fn save_png(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        self.write_png(BufWriter::new(File::create(path)?))
+    }
+}
+
+/// Adds `weight` times the length of `span` inside each pixel of a row.
+fn add_span(coverage: &mut [f32], span: (f32, f32), weight: f32) {
+    let width = coverage.len();
+    let (x0, x1) = (span.0.clamp(0.0, width as f32), span.1.clamp(0.0, width as f32));
+    let (first, last) = (x0.floor() as usize, x1.floor() as usize);
+    if first == last {
+        if first < width {
+            coverage[first] += (x1 - x0)*weight;
+        }
+        return
+    }
+    coverage[first] += (first as f32 + 1.0 - x0)*weight;
+    for c in &mut coverage[first + 1..last] {
+        *c += weight;
+    }
+    if last < width {
+        coverage[last] += (x1 - last as f32)*weight;
+    }
+}
+
+/// Strokes paths of pixel coordinates as one shape, so joins and crossings are not painted twice.
+fn stroke_paths(canvas: &mut Canvas, paths: &[Vec<Point>], closed: bool, color: [u8; 4], options: &RasterOptions) {
+    let triangles = paths.iter().flat_map(|points| stroke::stroke(points, closed, options.line_width, options.join)).collect::<Vec<[Point; 3]>>();
+    let triangles = triangles.iter().map(|t| &t[..]).collect::<Vec<&[Point]>>();
+    canvas.union(&triangles, color);
+}
+
+/// Maps unit-circle coordinates to pixels, with the circumcircle centred in the canvas.
+fn to_pixel(p: Point, options: &RasterOptions) -> Point {
+    Point::new(options.width as f32/2.0 + p.x*options.scale, options.height as f32/2.0 - p.y*options.scale)
+}
+
+/// Paints the nested outline of every winding level in turn, so each pixel ends up with the colour of the innermost level around it.
+fn fill_levels(canvas: &mut Canvas, polygon: &Polygon, mode: FillMode, options: &RasterOptions) {
+    let density = polygon.density();
+    let levels = if mode == FillMode::NonZero { density.min(1) } else { density };
+    for j in 1..=levels {
+        let color = match mode {
+            FillMode::Density => {
+                let [r, g, b] = fill::density_color(j, density).map(|c| (c*255.0).round() as u8);
+                [r, g, b, 255]
+            },
+            _ if mode.fills(j) => options.fill_color,
+            _ => options.background
+        };
+        let outline = fill::level_outline(polygon, j).into_iter().map(|p| to_pixel(p, options)).collect::<Vec<Point>>();
+        canvas.polygon(&outline, color);
+    }
+}
+
+pub fn rasterize(polygon: &Polygon, options: &RasterOptions) -> io::Result<Canvas> {
+    let mut canvas = Canvas::new(options.width, options.height, options.background)?;
+    if let Some(mode) = options.fill {
+        fill_levels(&mut canvas, polygon, mode, options);
+    }
+    let vertices = polygon.vertices().into_iter().map(|p| to_pixel(p, options)).collect::<Vec<Point>>();
+    let colors = &options.component_colors;
+    let compound = polygon.components() > 1 && !colors.is_empty();
+    if options.outline {
+        let outline = polygon.outline().into_iter().map(|p| to_pixel(p, options)).collect();
+        stroke_paths(&mut canvas, &[outline], true, options.color, options);
+    } else {
+        let loops = polygon.cycles().into_iter().map(|cycle| cycle.into_iter().map(|i| vertices[i]).collect()).collect::<Vec<Vec<Point>>>();
+        if compound {
+            for (c, path) in loops.iter().enumerate() {
+                stroke_paths(&mut canvas, std::slice::from_ref(path), true, colors[c % colors.len()], options);
+            }
+        } else {
+            stroke_paths(&mut canvas, &loops, true, options.color, options);
+        }
+    }
+    if let Some(radius) = options.vertex_radius {
+        for (i, &vertex) in vertices.iter().enumerate() {
+            let color = if compound { colors[polygon.component_of(i) % colors.len()] } else { options.vertex_color };
+            canvas.disc(vertex, radius, color);
+        }
+    }
+    if let Some(radius) = options.intersection_radius {
+        for point in polygon.intersections() {
+            canvas.disc(to_pixel(point, options), radius, options.intersection_color);
+        }
+    }
+    Ok(canvas)
+}
+
+pub fn write_png(path: impl AsRef<Path>, polygon: &Polygon, options: &RasterOptions) -> io::Result<()> {
+    rasterize(polygon, options)?.save_png(path)
+}
+
+/// Polylines and dots, such as a tiling or a chord diagram, with the polygon's stroke and vertex settings.
+/// The lines are joined back to their starts if `closed` is set. Fills, outlines and intersections don't apply.
+pub fn rasterize_lines(vertices: &[Point], lines: &[Vec<Point>], closed: bool, options: &RasterOptions) -> io::Result<Canvas> {
+    let mut canvas = Canvas::new(options.width, options.height, options.background)?;
+    let lines = lines.iter().map(|line| line.iter().map(|&p| to_pixel(p, options)).collect()).collect::<Vec<Vec<Point>>>();
+    stroke_paths(&mut canvas, &lines, closed, options.color, options);
+    if let Some(radius) = options.vertex_radius {
+        for &vertex in vertices {
+            canvas.disc(to_pixel(vertex, options), radius, options.vertex_color);
+        }
+    }
+    Ok(canvas)
+}
+
+pub fn write_lines_png(path: impl AsRef<Path>, vertices: &[Point], lines: &[Vec<Point>], closed: bool, options: &RasterOptions) -> io::Result<()> {
+    rasterize_lines(vertices, lines, closed, options)?.save_png(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn huge_disc() {
+        let mut canvas = Canvas::new(4, 3, [0, 0, 0, 255]).unwrap();
+        canvas.disc(Point::new(2.0, 1.5), 1e9, [255, 255, 255, 255]);
+        assert!(canvas.pixels.iter().all(|&c| c == 255));
+        let mut canvas = Canvas::new(4, 3, [0, 0, 0, 255]).unwrap();
+        canvas.disc(Point::new(-1e9, 1e9), 10.0, [255, 255, 255, 255]);
+        assert_eq!(canvas, Canvas::new(4, 3, [0, 0, 0, 255]).unwrap());
+    }
+}
@@ -0,0 +1,127 @@
+//! Thick lines as triangles, for renderers that can only fill.
+
+use crate::geometry::Point;
+use std::f32::consts::PI;
+
+/// How two segments of a path meet, as in SVG's `stroke-linejoin`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Join {
+    Miter,
+    Round,
+    Bevel
+}
+impl Join {
+    pub fn name(self) -> &'static str {
+        match self {
+            Join::Miter => "miter",
+            Join::Round => "round",
+            Join::Bevel => "bevel"
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Join::Miter => Join::Round,
+            Join::Round => Join::Bevel,
+            Join::Bevel => Join::Miter
+        }
+    }
+}
+
+/// Longest miter as a multiple of the stroke width before it is bevelled instead, SVG's default.
+pub const MITER_LIMIT: f32 = 4.0;
+
+/// Round joins use one triangle per this much turning.
+const ROUND_STEP: f32 = PI/8.0;
+
+fn along(p: Point, direction: Point, distance: f32) -> Point {
+    Point::new(p.x + direction.x*distance, p.y + direction.y*distance)
+}
+
+/// Unit normal to the left of `a -> b`, or `None` if they coincide.
+fn normal(a: Point, b: Point) -> Option<Point> {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let length = (dx*dx + dy*dy).sqrt();
+    (length > f32::EPSILON).then(|| Point::new(-dy/length, dx/length))
+}
+
+/// Triangles covering a stroke of the given width along `points`, with butt ends unless `closed`.
+/// Repeated points are skipped over. The triangles overlap, so draw them opaque or as a union.
+pub fn stroke(points: &[Point], closed: bool, width: f32, join: Join) -> Vec<[Point; 3]> {
+    stroke_offsets(points, closed, join).into_iter().map(|triangle| triangle.map(|(p, offset)| along(p, offset, width))).collect()
+}
+
+/// [`stroke`] for any width: every corner as a point of the path and how far it is from there per unit of
+/// width, so the same triangles can be widened or narrowed without tessellating the path again.
+pub fn stroke_offsets(points: &[Point], closed: bool, join: Join) -> Vec<[(Point, Point); 3]> {
+    let half = 0.5;
+    let len = points.len();
+    let mut triangles = Vec::new();
+    let segments = if closed { len } else { len.saturating_sub(1) };
+    for i in 0..segments {
+        let (a, b) = (points[i], points[(i + 1) % len]);
+        if let Some(normal) = normal(a, b) {
+            let (a0, a1) = ((a, normal.scale(half)), (a, normal.scale(-half)));
+            let (b0, b1) = ((b, normal.scale(half)), (b, normal.scale(-half)));
+            triangles.extend([[a0, a1, b1], [a0, b1, b0]]);
+        }
+    }
+    for (i, &p) in points.iter().enumerate() {
+        // The nearest distinct points either side, so repeated points still get a join.
+        let (before, after) = if closed { (len - 1, len - 1) } else { (i, len - i - 1) };
+        let previous = (1..=before).map(|d| points[(i + len - d) % len]).find(|&q| normal(q, p).is_some());
+        let next = (1..=after).map(|d| points[(i + d) % len]).find(|&q| normal(p, q).is_some());
+        if let (Some(previous), Some(next)) = (previous, next) {
+            join_triangles(previous, p, next, half, join, &mut triangles);
+        }
+    }
+    triangles
+}
+
+/// Fills the wedge on the outside of the corner at `p`.
+fn join_triangles(previous: Point, p: Point, next: Point, half: f32, join: Join, triangles: &mut Vec<[(Point, Point); 3]>) {
+    let (n0, n1) = match (normal(previous, p), normal(p, next)) {
+        (Some(n0), Some(n1)) => (n0, n1),
+        _ => return
+    };
+    let turn = n0.x*n1.y - n0.y*n1.x;
+    let dot = n0.x*n1.x + n0.y*n1.y;
+    if turn.abs() < 1e-6 && dot > 0.0 {
+        return
+    }
+    // Turning left opens a gap on the right.
+    let side = if turn > 0.0 { -half } else { half };
+    let (a, b) = ((p, n0.scale(side)), (p, n1.scale(side)));
+    let centre = (p, Point::new(0.0, 0.0));
+    match join {
+        Join::Bevel => triangles.push([centre, a, b]),
+        Join::Miter => {
+            let (bx, by) = (n0.x + n1.x, n0.y + n1.y);
+            let length = (bx*bx + by*by).sqrt();
+            // cos of the angle between either normal and the bisector; the miter reaches 1/cos half widths out.
+            let cos = length/2.0;
+            if length > f32::EPSILON && 1.0/cos <= MITER_LIMIT {
+                let tip = (p, Point::new(bx/length, by/length).scale(side/cos));
+                triangles.extend([[centre, a, tip], [centre, tip, b]]);
+            } else {
+                triangles.push([centre, a, b]);
+            }
+        },
+        Join::Round => {
+            let start = a.1.y.atan2(a.1.x);
+            let mut sweep = b.1.y.atan2(b.1.x) - start;
+            if sweep > PI {
+                sweep -= 2.0*PI;
+            } else if sweep < -PI {
+                sweep += 2.0*PI;
+            }
+            let steps = (sweep.abs()/ROUND_STEP).ceil().max(1.0) as usize;
+            let radius = side.abs();
+            let point = |i: usize| {
+                let (sin, cos) = (start + sweep*(i as f32)/(steps as f32)).sin_cos();
+                (p, Point::new(radius*cos, radius*sin))
+            };
+            triangles.extend((0..steps).map(|i| [centre, point(i), point(i + 1)]));
+        }
+    }
+}
@@ -0,0 +1,272 @@
+use crate::{
+    fill::{self, FillMode},
+    geometry::{Point, Polygon},
+    stroke::Join
+};
+use std::{
+    fmt::Write,
+    fs,
+    io,
+    path::Path
+};
+
+#[derive(Clone, Debug)]
+pub struct SvgOptions {
+    /// Circumradius in SVG user units.
+    pub scale: f32,
+    pub stroke_width: f32,
+    pub join: Join,
+    pub color: String,
+    pub background: Option<String>,
+    /// Radius of the dot drawn on every vertex, or `None` for no dots.
+    pub vertex_radius: Option<f32>,
+    pub vertex_color: String,
+    /// Radius of the dot drawn where two edges cross, or `None` for no dots.
+    pub intersection_radius: Option<f32>,
+    pub intersection_color: String,
+    /// Draw [`Polygon::outline`] instead of the crossing edges.
+    pub outline: bool,
+    pub fill: Option<FillMode>,
+    /// Used by every fill mode except [`FillMode::Density`], which colours by winding number.
+    pub fill_color: String,
+    /// Edge and vertex colour of each component of a compound in turn, repeating if there are fewer, in place of
+    /// `color` and `vertex_color`. Single paths and outlines, and everything drawn by [`lines_to_svg`], use those throughout.
+    pub component_colors: Vec<String>,
+    /// `[min-x, min-y, width, height]`; defaults to the circumcircle plus `margin` on every side.
+    pub view_box: Option<[f32; 4]>,
+    pub margin: f32
+}
+impl Default for SvgOptions {
+    fn default() -> Self {
+        SvgOptions {
+            scale: 100.0,
+            stroke_width: 1.0,
+            join: Join::Round,
+            color: "black".to_owned(),
+            background: None,
+            vertex_radius: Some(3.0),
+            vertex_color: "black".to_owned(),
+            intersection_radius: None,
+            intersection_color: "orange".to_owned(),
+            outline: false,
+            fill: None,
+            fill_color: "gray".to_owned(),
+            component_colors: Vec::new(),
+            view_box: None,
+            margin: 10.0
+        }
+    }
+}
+
+/// SVG y grows downwards, so points are mirrored to keep the on-screen orientation.
+fn to_svg_point(p: Point, scale: f32) -> Point {
+    Point::new(p.x*scale, -p.y*scale)
+}
+
+/// The opening tag and the background.
+fn start_svg(options: &SvgOptions) -> String {
+    let extent = options.scale + options.margin;
+    let [x, y, width, height] = options.view_box.unwrap_or([-extent, -extent, 2.0*extent, 2.0*extent]);
+    let mut svg = String::new();
+    writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}" width="{}" height="{}">"#, x, y, width, height, width, height).unwrap();
+    if let Some(background) = &options.background {
+        writeln!(svg, r#"  <rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#, x, y, width, height, background).unwrap();
+    }
+    svg
+}
+
+fn write_stroke(svg: &mut String, path: &str, color: &str, options: &SvgOptions) {
+    writeln!(
+        svg, r#"  <path d="{}" fill="none" stroke="{}" stroke-width="{}" stroke-linejoin="{}"/>"#,
+        path, color, options.stroke_width, options.join.name()
+    ).unwrap();
+}
+
+fn write_dots(svg: &mut String, points: &[Point], radius: f32, color: &str, scale: f32) {
+    writeln!(svg, r#"  <g fill="{}">"#, color).unwrap();
+    for &point in points {
+        let point = to_svg_point(point, scale);
+        writeln!(svg, r#"    <circle cx="{:.3}" cy="{:.3}" r="{}"/>"#, point.x, point.y, radius).unwrap();
+    }
+    writeln!(svg, "  </g>").unwrap();
+}
+
+pub fn to_svg(polygon: &Polygon, options: &SvgOptions) -> String {
+    let mut svg = start_svg(options);
+    if let Some(mode) = options.fill {
+        write_fill(&mut svg, polygon, mode, options);
+    }
+    let colors = &options.component_colors;
+    let compound = polygon.components() > 1 && !colors.is_empty();
+    // One closed subpath per component, so every corner gets a join, and one path each when they differ in colour.
+    if options.outline {
+        write_stroke(&mut svg, &outline_path(&polygon.outline(), options.scale), &options.color, options);
+    } else {
+        let paths = polygon.paths().iter().map(|path| outline_path(path, options.scale)).collect::<Vec<String>>();
+        if compound {
+            for (c, path) in paths.iter().enumerate() {
+                write_stroke(&mut svg, path, &colors[c % colors.len()], options);
+            }
+        } else {
+            write_stroke(&mut svg, &paths.concat(), &options.color, options);
+        }
+    }
+    if let Some(radius) = options.vertex_radius {
+        let vertices = polygon.vertices();
+        if compound {
+            for c in 0..polygon.components() {
+                let points = vertices.iter().enumerate().filter(|&(i, _)| polygon.component_of(i) == c).map(|(_, &p)| p).collect::<Vec<Point>>();
+                write_dots(&mut svg, &points, radius, &colors[c % colors.len()], options.scale);
+            }
+        } else {
+            write_dots(&mut svg, &vertices, radius, &options.vertex_color, options.scale);
+        }
+    }
+    if let Some(radius) = options.intersection_radius {
+        write_dots(&mut svg, &polygon.intersections(), radius, &options.intersection_color, options.scale);
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Polylines and dots, such as a tiling or a chord diagram, with the polygon's stroke and vertex settings.
+/// The lines are joined back to their starts if `closed` is set. Fills, outlines and intersections don't apply.
+pub fn lines_to_svg(vertices: &[Point], lines: &[Vec<Point>], closed: bool, options: &SvgOptions) -> String {
+    let mut svg = start_svg(options);
+    let subpath = if closed { outline_path } else { polyline_path };
+    let path = lines.iter().map(|line| subpath(line, options.scale)).collect::<String>();
+    write_stroke(&mut svg, &path, &options.color, options);
+    if let Some(radius) = options.vertex_radius {
+        write_dots(&mut svg, vertices, radius, &options.vertex_color, options.scale);
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn polyline_path(points: &[Point], scale: f32) -> String {
+    let mut path = String::new();
+    for (i, p) in points.iter().enumerate() {
+        let p = to_svg_point(*p, scale);
+        write!(path, "{}{:.3} {:.3}", if i == 0 { 'M' } else { 'L' }, p.x, p.y).unwrap();
+    }
+    path
+}
+
+fn outline_path(points: &[Point], scale: f32) -> String {
+    let mut path = polyline_path(points, scale);
+    if !path.is_empty() {
+        path.push('Z');
+    }
+    path
+}
+
+/// Fills are drawn as the nested outlines of each winding level, which avoids seams between regions.
+fn write_fill(svg: &mut String, polygon: &Polygon, mode: FillMode, options: &SvgOptions) {
+    let density = polygon.density();
+    if density == 0 {
+        return
+    }
+    match mode {
+        FillMode::NonZero => {
+            let path = outline_path(&fill::level_outline(polygon, 1), options.scale);
+            writeln!(svg, r#"  <path d="{}" fill="{}"/>"#, path, options.fill_color).unwrap();
+        },
+        FillMode::EvenOdd => {
+            let path = (1..=density).map(|j| outline_path(&fill::level_outline(polygon, j), options.scale)).collect::<String>();
+            writeln!(svg, r#"  <path d="{}" fill="{}" fill-rule="evenodd"/>"#, path, options.fill_color).unwrap();
+        },
+        FillMode::Density => for j in 1..=density {
+            let [r, g, b] = fill::density_color(j, density).map(|c| (c*255.0).round() as u8);
+            let path = outline_path(&fill::level_outline(polygon, j), options.scale);
+            writeln!(svg, r##"  <path d="{}" fill="#{:02x}{:02x}{:02x}"/>"##, path, r, g, b).unwrap();
+        }
+    }
+}
+
+pub fn write_svg(path: impl AsRef<Path>, polygon: &Polygon, options: &SvgOptions) -> io::Result<()> {
+    fs::write(path, to_svg(polygon, options))
+}
+
+pub fn write_lines_svg(path: impl AsRef<Path>, vertices: &[Point], lines: &[Vec<Point>], closed: bool, options: &SvgOptions) -> io::Result<()> {
+    fs::write(path, lines_to_svg(vertices, lines, closed, options))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn svg(symbol: &str, options: SvgOptions) -> String {
+        to_svg(&symbol.parse().unwrap(), &options)
+    }
+
+    /// The `d` attribute of every stroked path.
+    fn strokes(svg: &str) -> Vec<&str> {
+        svg.lines().filter(|line| line.contains("stroke=")).map(|line| line.split('"').nth(1).unwrap()).collect()
+    }
+
+    #[test]
+    fn view_box() {
+        assert!(svg("5", SvgOptions::default()).starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="-110 -110 220 220" width="220" height="220">"#));
+        let options = SvgOptions {
+            view_box: Some([0.0, -50.0, 300.0, 100.0]),
+            ..Default::default()
+        };
+        assert!(svg("5", options).contains(r#"viewBox="0 -50 300 100" width="300" height="100""#));
+    }
+
+    #[test]
+    fn subpath_per_component() {
+        let path = strokes(&svg("6/2", SvgOptions::default())).concat();
+        assert_eq!(path.matches('M').count(), 2);
+        assert_eq!(path.matches('Z').count(), 2);
+        assert_eq!(path.matches('L').count(), 4);
+        let outline = svg("6/2", SvgOptions {
+            outline: true,
+            ..Default::default()
+        });
+        assert_eq!(strokes(&outline).concat().matches('M').count(), 1);
+    }
+
+    #[test]
+    fn component_colors() {
+        let colors = |symbol: &str| {
+            let svg = svg(symbol, SvgOptions {
+                component_colors: vec!["red".to_owned(), "blue".to_owned()],
+                ..Default::default()
+            });
+            let strokes = svg.lines().filter_map(|line| line.split("stroke=\"").nth(1)).map(|s| s.split('"').next().unwrap().to_owned());
+            let dots = svg.lines().filter_map(|line| line.strip_prefix("  <g fill=\"")).map(|s| s.split('"').next().unwrap().to_owned());
+            (strokes.collect::<Vec<String>>(), dots.collect::<Vec<String>>())
+        };
+        assert_eq!(colors("6/2"), (vec!["red".to_owned(), "blue".to_owned()], vec!["red".to_owned(), "blue".to_owned()]));
+        assert_eq!(colors("3{4}"), (vec!["red".to_owned(), "blue".to_owned(), "red".to_owned()], vec!["red".to_owned(), "blue".to_owned(), "red".to_owned()]));
+        assert_eq!(colors("5/2"), (vec!["black".to_owned()], vec!["black".to_owned()]));
+    }
+
+    #[test]
+    fn vertex_dots() {
+        assert_eq!(svg("7/3", SvgOptions::default()).matches("<circle").count(), 7);
+        assert_eq!(svg("3{5/2}", SvgOptions::default()).matches("<circle").count(), 15);
+        let options = SvgOptions {
+            vertex_radius: None,
+            intersection_radius: Some(2.0),
+            ..Default::default()
+        };
+        assert_eq!(svg("5/2", options).matches("<circle").count(), 5);
+    }
+
+    #[test]
+    fn fill_rule() {
+        let fill = |mode| svg("7/3", SvgOptions {
+            fill: Some(mode),
+            ..Default::default()
+        });
+        let even_odd = fill(FillMode::EvenOdd);
+        assert!(even_odd.contains(r#"fill-rule="evenodd""#));
+        assert_eq!(even_odd.lines().nth(1).unwrap().matches('M').count(), 3);
+        let non_zero = fill(FillMode::NonZero);
+        assert!(!non_zero.contains("fill-rule"));
+        assert_eq!(non_zero.lines().nth(1).unwrap().matches('M').count(), 1);
+        assert_eq!(fill(FillMode::Density).matches(r##"fill="#"##).count(), 3);
+    }
+}
@@ -0,0 +1,183 @@
+//! Everything the input field and the command line accept: star polygons, tilings, chord diagrams, derived
+//! polygons and curves, and why a symbol fails to parse. The `{n/k}` grammar has its own [`ParseError`],
+//! which these errors wrap.
+
+use crate::{
+    chords::{ChordError, ChordRule},
+    curves::{Curve, CurveError},
+    geometry::{ParseError, ParseErrorKind, Point, Polygon},
+    operators::{Derived, OperatorError},
+    tiling::{Tiling, TilingError},
+    variant::Variant
+};
+use std::{
+    fmt,
+    ops::Range,
+    str::FromStr
+};
+
+/// A star polygon, a tiling when there is a comma, a chord diagram when there is a colon, a curve when there
+/// are parentheses, or a polygon derived by operators in front of it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Symbol {
+    Polygon(Polygon),
+    Tiling(Tiling),
+    Chords(ChordRule),
+    Derived(Derived),
+    Curve(Curve)
+}
+impl Symbol {
+    /// The dots and lines of anything but a plain polygon, which the exporters draw alike, and whether the
+    /// lines are closed. Polygons have exporters of their own unless drawn as a variant; a curve has no dots
+    /// but those of the polygon it overlays.
+    pub fn lines(&self, variant: Option<Variant>, overlay: Option<&Polygon>) -> Option<(Vec<Point>, Vec<Vec<Point>>, bool)> {
+        let closed = |cycles: Vec<Vec<Point>>| Some((cycles.concat(), cycles, true));
+        match self {
+            Symbol::Polygon(polygon) => variant.and_then(|variant| closed(variant.cycles(polygon))),
+            Symbol::Tiling(tiling) => {
+                let patch = tiling.patch();
+                Some((patch.vertices.clone(), patch.edges.clone(), false))
+            },
+            Symbol::Chords(rule) => {
+                let vertices = rule.vertices();
+                let lines = rule.edges().into_iter().map(|[a, b]| vec![vertices[a], vertices[b]]).collect();
+                Some((vertices, lines, false))
+            },
+            Symbol::Derived(derived) => closed(derived.cycles().to_vec()),
+            Symbol::Curve(curve) => {
+                let cycles = overlay.map(Polygon::paths).unwrap_or_default();
+                Some((cycles.concat(), [vec![curve.points()], cycles].concat(), true))
+            }
+        }
+    }
+
+    /// A name for exported files without the extension, such as `shaper_5_2` for `{5/2}`.
+    pub fn file_stem(&self, variant: Option<Variant>) -> String {
+        let symbol = self.to_string();
+        // Operators such as the `t` of `t{5/2}`, and the name of a curve, stay in front.
+        let (operators, symbol) = symbol.split_at(symbol.find(|c: char| !c.is_ascii_lowercase()).unwrap_or(0));
+        let symbol = symbol.strip_prefix('{').unwrap_or(symbol);
+        let symbol = symbol.replace('{', "x").replace(['}', ')'], "").replace('/', "_").replace([',', ':', '('], "-").replace('*', "x");
+        let stem = format!("shaper_{}{}", operators, symbol.replace(' ', ""));
+        match variant {
+            Some(variant) => format!("{}-{}-{:.2}", stem, variant.name(), variant.value()),
+            None => stem
+        }
+    }
+}
+impl FromStr for Symbol {
+    type Err = SymbolError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.contains(':') {
+            s.parse().map(Symbol::Chords)
+        } else if s.contains('(') {
+            s.parse().map(Symbol::Curve)
+        } else if s.contains(',') {
+            s.parse().map(Symbol::Tiling)
+        } else if s.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            // Operators that cancel out, as in `dd{5}`, leave the polygon itself.
+            s.parse::<Derived>().map(|derived| match derived.operators() {
+                [] => Symbol::Polygon(derived.polygon().clone()),
+                _ => Symbol::Derived(derived)
+            })
+        } else {
+            Ok(Symbol::Polygon(s.parse()?))
+        }
+    }
+}
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Symbol::Polygon(polygon) => polygon.fmt(f),
+            Symbol::Tiling(tiling) => tiling.fmt(f),
+            Symbol::Chords(rule) => rule.fmt(f),
+            Symbol::Derived(derived) => derived.fmt(f),
+            Symbol::Curve(curve) => curve.fmt(f)
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SymbolErrorKind {
+    /// A part written like a polygon or a number, such as `q` of `{p,q}` or the `n` of `n:rule`.
+    Polygon(ParseErrorKind),
+    MissingComma,
+    TooManyCommas,
+    MissingColon,
+    ExpectedOperand,
+    UnclosedParenthesis,
+    UnknownOperator(char),
+    UnknownCurve,
+    CurveArguments,
+    /// A well-formed symbol for something that can't exist, with the reason from the module that builds it.
+    Tiling(TilingError),
+    Chords(ChordError),
+    Operator(OperatorError),
+    Curve(CurveError)
+}
+impl From<ParseErrorKind> for SymbolErrorKind {
+    fn from(e: ParseErrorKind) -> Self {
+        SymbolErrorKind::Polygon(e)
+    }
+}
+impl From<TilingError> for SymbolErrorKind {
+    fn from(e: TilingError) -> Self {
+        SymbolErrorKind::Tiling(e)
+    }
+}
+impl From<ChordError> for SymbolErrorKind {
+    fn from(e: ChordError) -> Self {
+        SymbolErrorKind::Chords(e)
+    }
+}
+impl From<OperatorError> for SymbolErrorKind {
+    fn from(e: OperatorError) -> Self {
+        SymbolErrorKind::Operator(e)
+    }
+}
+impl From<CurveError> for SymbolErrorKind {
+    fn from(e: CurveError) -> Self {
+        SymbolErrorKind::Curve(e)
+    }
+}
+
+/// Why a symbol failed to parse, and the byte range of the input it concerns.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SymbolError {
+    pub span: Range<usize>,
+    pub kind: SymbolErrorKind
+}
+impl SymbolError {
+    pub fn new(span: Range<usize>, kind: SymbolErrorKind) -> Self {
+        SymbolError {
+            span,
+            kind
+        }
+    }
+}
+impl From<ParseError> for SymbolError {
+    fn from(e: ParseError) -> Self {
+        SymbolError::new(e.span, e.kind.into())
+    }
+}
+impl fmt::Display for SymbolError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            SymbolErrorKind::Polygon(kind) => ParseError::new(self.span.clone(), kind.clone()).fmt(f),
+            SymbolErrorKind::MissingComma => write!(f, "expected ',' between p and q"),
+            SymbolErrorKind::TooManyCommas => write!(f, "only one ',' is allowed"),
+            SymbolErrorKind::MissingColon => write!(f, "expected ':' after the number of points"),
+            SymbolErrorKind::ExpectedOperand => write!(f, "expected a number, i, n or '('"),
+            SymbolErrorKind::UnclosedParenthesis => write!(f, "missing closing ')'"),
+            SymbolErrorKind::UnknownOperator(ch) => write!(f, "unknown operator '{}', expected t, r, d or s", ch),
+            SymbolErrorKind::UnknownCurve => write!(f, "expected hypo, epi or rose followed by '('"),
+            SymbolErrorKind::CurveArguments => write!(f, "expected R, r and d, or n/d for a rose"),
+            SymbolErrorKind::Tiling(e) => e.fmt(f),
+            SymbolErrorKind::Chords(e) => e.fmt(f),
+            SymbolErrorKind::Operator(e) => e.fmt(f),
+            SymbolErrorKind::Curve(e) => e.fmt(f)
+        }
+    }
+}
+impl std::error::Error for SymbolError {}
@@ -0,0 +1,459 @@
+//! Regular tilings `{p,q}`: faces `{p}` meeting `q` to a vertex, where either may be a star.
+//!
+//! Depending on the angles the faces close up on a sphere, the Euclidean plane or the hyperbolic plane.
+//! Faces are found by reflecting the first one in its edges over and over. The reflections are 3×3 matrices
+//! acting on the plane `z = 1`, the unit sphere or the hyperboloid `z² - x² - y² = 1`. The result is flattened into
+//! the unit circle as a patch of the plane, the Poincaré disk, or the sphere projected from its south pole.
+
+use crate::{
+    geometry::{parse_body, ParseErrorKind, Point, Polygon},
+    symbol::{SymbolError, SymbolErrorKind}
+};
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet, VecDeque},
+    f64::consts::PI,
+    fmt,
+    str::FromStr,
+    sync::Arc
+};
+
+/// How far the Euclidean patch reaches in face circumradii, before it is shrunk to fit the unit circle.
+const EUCLIDEAN_PATCH: f64 = 5.0;
+/// Hyperbolic faces are drawn while their centre lies within this radius of the Poincaré disk.
+const HYPERBOLIC_PATCH: f64 = 0.97;
+/// Projected spherical points further out than this are dropped, as they run off to infinity near the south pole.
+const SPHERICAL_CLIP: f64 = 5.0;
+/// Curved edges are drawn as this many straight pieces.
+const EDGE_SEGMENTS: usize = 16;
+/// More faces than this in a patch means they never close up: each new face lands between the others.
+const MAX_FACES: usize = 2000;
+/// Most vertices of a face or a vertex figure. Faces with more than this are already too big for a second one
+/// to fit in the Poincaré disk, and each costs time and memory in proportion to its size.
+pub const MAX_VERTICES: usize = 100;
+/// Model points closer than this are the same point.
+const EPSILON: f64 = 1e-6;
+
+type Vector = [f64; 3];
+type Matrix = [[f64; 3]; 3];
+
+const ORIGIN: Vector = [0.0, 0.0, 1.0];
+const IDENTITY: Matrix = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Geometry {
+    Spherical,
+    Euclidean,
+    Hyperbolic
+}
+impl Geometry {
+    pub fn name(self) -> &'static str {
+        match self {
+            Geometry::Spherical => "spherical",
+            Geometry::Euclidean => "Euclidean",
+            Geometry::Hyperbolic => "hyperbolic"
+        }
+    }
+
+    /// Moves the origin a distance `d` along the x axis.
+    fn translation(self, d: f64) -> Matrix {
+        match self {
+            Geometry::Spherical => [[d.cos(), 0.0, d.sin()], [0.0, 1.0, 0.0], [-d.sin(), 0.0, d.cos()]],
+            Geometry::Euclidean => [[1.0, 0.0, d], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+            Geometry::Hyperbolic => [[d.cosh(), 0.0, d.sinh()], [0.0, 1.0, 0.0], [d.sinh(), 0.0, d.cosh()]]
+        }
+    }
+
+    /// Reflection in the line at distance `d` from the origin whose nearest point is in direction `angle`.
+    fn reflection(self, d: f64, angle: f64) -> Matrix {
+        let mirror = [[-1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+        [rotation(angle), self.translation(d), mirror, self.translation(-d), rotation(-angle)].iter().fold(IDENTITY, |m, n| multiply(&m, n))
+    }
+
+    /// Scales `v` back onto the model surface, so that a blend of two points lies on the line between them.
+    fn normalise(self, v: Vector) -> Vector {
+        let length = match self {
+            Geometry::Spherical => (v[0]*v[0] + v[1]*v[1] + v[2]*v[2]).sqrt(),
+            Geometry::Euclidean => v[2],
+            Geometry::Hyperbolic => (v[2]*v[2] - v[0]*v[0] - v[1]*v[1]).sqrt()
+        };
+        v.map(|x| x/length)
+    }
+
+    /// Where `v` ends up in the unit circle, or `None` when it is too far out to draw.
+    fn project(self, v: Vector) -> Option<Point> {
+        let (x, y) = match self {
+            Geometry::Euclidean => (v[0]/EUCLIDEAN_PATCH, v[1]/EUCLIDEAN_PATCH),
+            _ => (v[0]/(1.0 + v[2]), v[1]/(1.0 + v[2]))
+        };
+        (x.hypot(y) <= SPHERICAL_CLIP).then(|| Point::new(x as f32, y as f32))
+    }
+
+    /// Whether a face centred on `v` belongs to the patch; the sphere is always drawn whole.
+    fn in_patch(self, v: Vector) -> bool {
+        match self {
+            Geometry::Spherical => true,
+            Geometry::Euclidean => v[0].hypot(v[1]) <= EUCLIDEAN_PATCH,
+            Geometry::Hyperbolic => v[0].hypot(v[1])/(1.0 + v[2]) <= HYPERBOLIC_PATCH
+        }
+    }
+}
+
+fn rotation(angle: f64) -> Matrix {
+    let (sin, cos) = angle.sin_cos();
+    [[cos, -sin, 0.0], [sin, cos, 0.0], [0.0, 0.0, 1.0]]
+}
+
+fn multiply(a: &Matrix, b: &Matrix) -> Matrix {
+    let mut m = [[0.0; 3]; 3];
+    for (i, row) in m.iter_mut().enumerate() {
+        for (j, x) in row.iter_mut().enumerate() {
+            *x = (0..3).map(|l| a[i][l]*b[l][j]).sum();
+        }
+    }
+    m
+}
+
+fn apply(m: &Matrix, v: Vector) -> Vector {
+    m.map(|row| row[0]*v[0] + row[1]*v[1] + row[2]*v[2])
+}
+
+/// Model points up to rounding, numbered in the order they are first seen.
+#[derive(Default)]
+struct PointSet {
+    cells: HashMap<[i64; 3], usize>
+}
+impl PointSet {
+    /// The number of `v`, and whether it is new.
+    fn insert(&mut self, v: Vector) -> (usize, bool) {
+        let cell = v.map(|x| (x/EPSILON).round() as i64);
+        // A point on the edge of a cell may have been rounded into the next one.
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                for dz in -1..=1 {
+                    if let Some(&i) = self.cells.get(&[cell[0] + dx, cell[1] + dy, cell[2] + dz]) {
+                        return (i, false)
+                    }
+                }
+            }
+        }
+        let i = self.cells.len();
+        self.cells.insert(cell, i);
+        (i, true)
+    }
+}
+
+/// What a tiling looks like within the unit circle, ready to be drawn like a polygon.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Patch {
+    pub faces: usize,
+    pub vertices: Vec<Point>,
+    /// Every edge once, as a polyline since edges are arcs outside the Euclidean plane.
+    pub edges: Vec<Vec<Point>>
+}
+
+/// A regular tiling `{p,q}`, only constructible where the faces close up around every vertex and around each other.
+///
+/// The patch found while checking that is kept, and shared between clones.
+#[derive(Clone, Debug)]
+pub struct Tiling {
+    p: Polygon,
+    q: Polygon,
+    patch: Arc<Patch>
+}
+impl Tiling {
+    /// `p` and `q` are taken with their smaller step, as `{5/3}` is the same shape as `{5/2}`.
+    pub fn new(p: Polygon, q: Polygon) -> Result<Self, TilingError> {
+        if p.components() > 1 {
+            return Err(TilingError::CompoundFace)
+        }
+        if q.components() > 1 {
+            return Err(TilingError::CompoundVertexFigure)
+        }
+        if p.n() > MAX_VERTICES {
+            return Err(TilingError::FaceTooLarge)
+        }
+        if q.n() > MAX_VERTICES {
+            return Err(TilingError::VertexFigureTooLarge)
+        }
+        let smaller = |p: Polygon| Polygon::new(p.n(), p.k().min(p.n() - p.k())).unwrap();
+        let (p, q) = (smaller(p), smaller(q));
+        let patch = generate(&p, &q).ok_or(TilingError::NotDiscrete)?;
+        Ok(Tiling {
+            p,
+            q,
+            patch: Arc::new(patch)
+        })
+    }
+
+    /// The face.
+    pub fn p(&self) -> &Polygon {
+        &self.p
+    }
+
+    /// The vertex figure, the polygon joining the neighbours of a vertex.
+    pub fn q(&self) -> &Polygon {
+        &self.q
+    }
+
+    /// Spherical when the angles `πk/n` at a face's centre and `π/q` at a vertex add up to more than a right angle.
+    pub fn geometry(&self) -> Geometry {
+        geometry(&self.p, &self.q)
+    }
+
+    /// Distance from the centre of a face to its vertices, on the unit sphere or the hyperbolic plane of curvature -1.
+    /// Euclidean tilings have no natural size, so their faces have circumradius 1.
+    pub fn circumradius(&self) -> f32 {
+        circumradius(&self.p, &self.q) as f32
+    }
+
+    /// Distance from the centre of a face to its edges, in the same units as [`Tiling::circumradius`].
+    pub fn inradius(&self) -> f32 {
+        inradius(&self.p, &self.q) as f32
+    }
+
+    /// The faces around the origin, centred on a face with a vertex on the positive x axis.
+    pub fn patch(&self) -> &Patch {
+        &self.patch
+    }
+}
+/// The patch follows from `p` and `q`.
+impl PartialEq for Tiling {
+    fn eq(&self, other: &Self) -> bool {
+        self.p == other.p && self.q == other.q
+    }
+}
+impl Eq for Tiling {}
+
+fn geometry(p: &Polygon, q: &Polygon) -> Geometry {
+    let [n, k, m, j] = [p.n(), p.k(), q.n(), q.k()].map(|x| x as u128);
+    match (2*(k*m + j*n)).cmp(&(n*m)) {
+        Ordering::Greater => Geometry::Spherical,
+        Ordering::Equal => Geometry::Euclidean,
+        Ordering::Less => Geometry::Hyperbolic
+    }
+}
+
+/// Half the angles at a face's centre and at a vertex, with the right angle at an edge's midpoint making up a
+/// triangle that repeats throughout the tiling.
+fn angles(p: &Polygon, q: &Polygon) -> (f64, f64) {
+    let angle = |p: &Polygon| PI*(p.k() as f64)/(p.n() as f64);
+    (angle(p), angle(q))
+}
+
+fn circumradius(p: &Polygon, q: &Polygon) -> f64 {
+    let (a, b) = angles(p, q);
+    let c = 1.0/(a.tan()*b.tan());
+    match geometry(p, q) {
+        Geometry::Spherical => c.acos(),
+        Geometry::Euclidean => 1.0,
+        Geometry::Hyperbolic => c.acosh()
+    }
+}
+
+fn inradius(p: &Polygon, q: &Polygon) -> f64 {
+    let (a, b) = angles(p, q);
+    let c = b.cos()/a.sin();
+    match geometry(p, q) {
+        Geometry::Spherical => c.acos(),
+        Geometry::Euclidean => a.cos(),
+        Geometry::Hyperbolic => c.acosh()
+    }
+}
+
+/// Breadth-first over faces from the one at the origin, or `None` if they never stop coming.
+fn generate(p: &Polygon, q: &Polygon) -> Option<Patch> {
+    let geometry = geometry(p, q);
+    let (n, k) = (p.n(), p.k());
+    let corner = apply(&geometry.translation(circumradius(p, q)), ORIGIN);
+    let corners = (0..n).map(|i| apply(&rotation(2.0*PI*(i as f64)/(n as f64)), corner)).collect::<Vec<Vector>>();
+    // Edge i joins corners i and i + k, with its midpoint halfway round between them.
+    let inradius = inradius(p, q);
+    let mirrors = (0..n).map(|i| geometry.reflection(inradius, PI*((2*i + k) as f64)/(n as f64))).collect::<Vec<Matrix>>();
+
+    let mut patch = Patch::default();
+    let mut centres = PointSet::default();
+    let mut vertices = PointSet::default();
+    let mut edges = HashSet::new();
+    centres.insert(ORIGIN);
+    let mut queue = VecDeque::from([IDENTITY]);
+    while let Some(face) = queue.pop_front() {
+        patch.faces += 1;
+        if patch.faces > MAX_FACES {
+            return None
+        }
+        let corners = corners.iter().map(|&c| apply(&face, c)).collect::<Vec<Vector>>();
+        let ids = corners.iter().map(|&c| {
+            let (id, new) = vertices.insert(c);
+            if new {
+                patch.vertices.extend(geometry.project(c));
+            }
+            id
+        }).collect::<Vec<usize>>();
+        for i in 0..n {
+            let j = (i + k) % n;
+            if edges.insert((ids[i].min(ids[j]), ids[i].max(ids[j]))) {
+                patch.edges.extend(edge(geometry, corners[i], corners[j]));
+            }
+        }
+        for mirror in &mirrors {
+            let neighbour = multiply(&face, mirror);
+            let centre = apply(&neighbour, ORIGIN);
+            if geometry.in_patch(centre) && centres.insert(centre).1 {
+                queue.push_back(neighbour);
+            }
+        }
+    }
+    Some(patch)
+}
+
+/// The projected line from `a` to `b`, split where it leaves the drawable part of the disk.
+fn edge(geometry: Geometry, a: Vector, b: Vector) -> Vec<Vec<Point>> {
+    let segments = if geometry == Geometry::Euclidean { 1 } else { EDGE_SEGMENTS };
+    let mut pieces = vec![Vec::new()];
+    for i in 0..=segments {
+        let t = (i as f64)/(segments as f64);
+        let v = geometry.normalise([0, 1, 2].map(|c| a[c] + (b[c] - a[c])*t));
+        match geometry.project(v) {
+            Some(p) => pieces.last_mut().unwrap().push(p),
+            None => pieces.push(Vec::new())
+        }
+    }
+    pieces.retain(|piece| piece.len() > 1);
+    pieces
+}
+
+/// `n` or `n/k`, as inside a Schläfli symbol.
+fn fraction(p: &Polygon) -> String {
+    if p.k() == 1 { p.n().to_string() } else { format!("{}/{}", p.n(), p.k()) }
+}
+
+impl fmt::Display for Tiling {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{{{},{}}}", fraction(&self.p), fraction(&self.q))
+    }
+}
+
+/// Accepts `{p,q}` and `p,q`, where `p` and `q` are `n` or `n/k`.
+impl FromStr for Tiling {
+    type Err = SymbolError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err(SymbolError::new(0..0, ParseErrorKind::Empty.into()))
+        }
+        let (offset, end) = match s.strip_prefix('{') {
+            Some(rest) => match rest.find('}') {
+                Some(i) => {
+                    let close = 1 + i;
+                    if let Some(ch) = s[close + 1..].chars().next() {
+                        return Err(SymbolError::new(close + 1..close + 1 + ch.len_utf8(), ParseErrorKind::InvalidCharacter(ch).into()))
+                    }
+                    (1, close)
+                },
+                None => return Err(SymbolError::new(s.len()..s.len(), ParseErrorKind::UnclosedBrace.into()))
+            },
+            None => (0, s.len())
+        };
+        let commas = s[offset..end].match_indices(',').map(|(i, _)| offset + i).collect::<Vec<usize>>();
+        let comma = match commas[..] {
+            [] => return Err(SymbolError::new(end..end, SymbolErrorKind::MissingComma)),
+            [comma] => comma,
+            [_, second, ..] => return Err(SymbolError::new(second..end, SymbolErrorKind::TooManyCommas))
+        };
+        let p = parse_body(&s[offset..comma], offset, 1)?;
+        let q = parse_body(&s[comma + 1..end], comma + 1, 1)?;
+        Tiling::new(p, q).map_err(|e| {
+            let span = match e {
+                TilingError::CompoundFace | TilingError::FaceTooLarge => offset..comma,
+                TilingError::CompoundVertexFigure | TilingError::VertexFigureTooLarge => comma + 1..end,
+                TilingError::NotDiscrete => 0..s.len()
+            };
+            SymbolError::new(span, e.into())
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TilingError {
+    CompoundFace,
+    CompoundVertexFigure,
+    FaceTooLarge,
+    VertexFigureTooLarge,
+    NotDiscrete
+}
+impl fmt::Display for TilingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TilingError::CompoundFace => write!(f, "faces can't be compounds"),
+            TilingError::CompoundVertexFigure => write!(f, "the vertex figure can't be a compound"),
+            TilingError::FaceTooLarge => write!(f, "faces can have at most {} vertices", MAX_VERTICES),
+            TilingError::VertexFigureTooLarge => write!(f, "at most {} faces can meet at a vertex", MAX_VERTICES),
+            TilingError::NotDiscrete => write!(f, "these faces never close up into a tiling")
+        }
+    }
+}
+impl std::error::Error for TilingError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ops::Range;
+
+    fn tiling(s: &str) -> Tiling {
+        s.parse().unwrap()
+    }
+
+    fn error(s: &str) -> (Range<usize>, SymbolErrorKind) {
+        let e = s.parse::<Tiling>().unwrap_err();
+        (e.span, e.kind)
+    }
+
+    #[test]
+    fn geometry() {
+        assert_eq!(tiling("{4,4}").geometry(), Geometry::Euclidean);
+        assert_eq!(tiling("{6,3}").geometry(), Geometry::Euclidean);
+        assert_eq!(tiling("{5,4}").geometry(), Geometry::Hyperbolic);
+        assert_eq!(tiling("{3,5}").geometry(), Geometry::Spherical);
+        assert_eq!(tiling("{5/2,5}").geometry(), Geometry::Spherical);
+    }
+
+    #[test]
+    fn smaller_step() {
+        assert_eq!(tiling("{5/3,5}"), tiling("{5/2,5}"));
+        assert_eq!(tiling("5/3,5").to_string(), "{5/2,5}");
+    }
+
+    #[test]
+    fn syntax() {
+        assert_eq!(error(""), (0..0, SymbolErrorKind::Polygon(ParseErrorKind::Empty)));
+        assert_eq!(error("{4,4"), (4..4, SymbolErrorKind::Polygon(ParseErrorKind::UnclosedBrace)));
+        assert_eq!(error("{4,4}x"), (5..6, SymbolErrorKind::Polygon(ParseErrorKind::InvalidCharacter('x'))));
+        assert_eq!(error("{4}"), (2..2, SymbolErrorKind::MissingComma));
+        assert_eq!(error("{4,4,4}"), (4..6, SymbolErrorKind::TooManyCommas));
+        assert_eq!(error("{4,x}"), (3..4, SymbolErrorKind::Polygon(ParseErrorKind::InvalidCharacter('x'))));
+    }
+
+    #[test]
+    fn impossible() {
+        assert_eq!(error("{6/2,3}"), (1..4, SymbolErrorKind::Tiling(TilingError::CompoundFace)));
+        assert_eq!(error("{4,6/2}"), (3..6, SymbolErrorKind::Tiling(TilingError::CompoundVertexFigure)));
+        assert_eq!(error("{101,3}"), (1..4, SymbolErrorKind::Tiling(TilingError::FaceTooLarge)));
+        assert_eq!(error("{3,101}"), (3..6, SymbolErrorKind::Tiling(TilingError::VertexFigureTooLarge)));
+        assert_eq!(error("{7/3,3}"), (0..7, SymbolErrorKind::Tiling(TilingError::NotDiscrete)));
+    }
+
+    #[test]
+    fn radii() {
+        let radii = |s: &str| {
+            let tiling = tiling(s);
+            (tiling.circumradius(), tiling.inradius())
+        };
+        let close = |(a, b): (f32, f32), (c, d): (f32, f32)| (a - c).abs() < 1e-4 && (b - d).abs() < 1e-4;
+        assert!(close(radii("{4,4}"), (1.0, std::f32::consts::FRAC_1_SQRT_2)));
+        // The cube's faces reach a quarter turn from their centre to the middle of an edge.
+        assert!(close(radii("{4,3}"), (0.95532, std::f32::consts::FRAC_PI_4)));
+        assert!(close(radii("{3,5}"), (0.65236, 0.36486)));
+        assert!(close(radii("{5,4}"), (0.84248, 0.62687)));
+    }
+}
@@ -0,0 +1,91 @@
+//! Relatives of a star polygon with two kinds of vertex or two kinds of edge, each varied by one number in `0..=1`.
+
+use crate::geometry::{Point, Polygon};
+use std::{
+    f32::consts::{PI, TAU},
+    fmt
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Variant {
+    /// An isotoxal star: `n` tips on the unit circle alternating with `n` dents between them, where a depth
+    /// of `0` puts the dents on the sides of the regular `n`-gon and `1` pulls them in to the centre.
+    Isotoxal(f32),
+    /// An isogonal polygon: every vertex of `{n/k}` split into two on the circle, `cut` of the way towards
+    /// the middle of the edges either side. `0` is `{n/k}` itself, `0.5` the regular truncation `{2n/k}`
+    /// and `1` the rectification, `{n/k}` turned by half an edge.
+    Isogonal(f32)
+}
+impl Variant {
+    /// The isotoxal star whose dents are the inner corners of [`Polygon::outline`], so it has the same tips as `polygon`.
+    pub fn isotoxal(polygon: &Polygon) -> Self {
+        let (n, k) = (polygon.n() as f32, polygon.k().min(polygon.n() - polygon.k()) as f32);
+        let dent = (PI*k/n).cos()/(PI*(k - 1.0)/n).cos();
+        Variant::Isotoxal((1.0 - dent/(PI/n).cos()).clamp(0.0, 1.0))
+    }
+
+    /// The regular truncation.
+    pub fn isogonal() -> Self {
+        Variant::Isogonal(0.5)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Variant::Isotoxal(_) => "isotoxal",
+            Variant::Isogonal(_) => "isogonal"
+        }
+    }
+
+    /// What the value varies: the depth of an isotoxal star or the cut of an isogonal polygon.
+    pub fn parameter_name(self) -> &'static str {
+        match self {
+            Variant::Isotoxal(_) => "depth",
+            Variant::Isogonal(_) => "cut"
+        }
+    }
+
+    pub fn value(self) -> f32 {
+        match self {
+            Variant::Isotoxal(value) | Variant::Isogonal(value) => value
+        }
+    }
+
+    /// The same kind of variant with another value, clamped to `0..=1`.
+    pub fn with_value(self, value: f32) -> Self {
+        let value = value.clamp(0.0, 1.0);
+        match self {
+            Variant::Isotoxal(_) => Variant::Isotoxal(value),
+            Variant::Isogonal(_) => Variant::Isogonal(value)
+        }
+    }
+
+    /// The closed paths to draw for `polygon`: one star, or one isogonal polygon per component.
+    pub fn cycles(self, polygon: &Polygon) -> Vec<Vec<Point>> {
+        let n = polygon.n() as f32;
+        let point = |angle: f32, radius: f32| {
+            let (sin, cos) = angle.sin_cos();
+            Point::new(radius*cos, radius*sin)
+        };
+        match self {
+            Variant::Isotoxal(depth) => {
+                let dent = (1.0 - depth)*(PI/n).cos();
+                vec![(0..polygon.n()).flat_map(|i| {
+                    let angle = TAU*(i as f32)/n;
+                    [point(angle, 1.0), point(angle + PI/n, dent)]
+                }).collect()]
+            },
+            Variant::Isogonal(cut) => {
+                let offset = cut*PI*(polygon.k() as f32)/n;
+                polygon.cycles().into_iter().map(|cycle| cycle.into_iter().flat_map(|i| {
+                    let angle = TAU*(i as f32)/n;
+                    [point(angle - offset, 1.0), point(angle + offset, 1.0)]
+                }).collect()).collect()
+            }
+        }
+    }
+}
+impl fmt::Display for Variant {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}, {} {:.2}", self.name(), self.parameter_name(), self.value())
+    }
+}
@@ -0,0 +1,240 @@
+use shaper_2d::{
+    curves::Curve,
+    fill::FillMode,
+    geometry::{Point, Polygon},
+    operators::Derived,
+    palette,
+    raster::{self, Canvas, RasterOptions},
+    symbol::Symbol,
+    variant::Variant
+};
+use std::{
+    env,
+    f32::consts::PI,
+    fs::{self, File},
+    path::PathBuf
+};
+
+const TOLERANCE: u8 = 2;
+
+fn options() -> RasterOptions {
+    RasterOptions {
+        width: 128,
+        height: 128,
+        scale: 56.0,
+        ..Default::default()
+    }
+}
+
+fn load_png(path: &PathBuf) -> Canvas {
+    let decoder = png::Decoder::new(File::open(path).unwrap());
+    let mut reader = decoder.read_info().unwrap();
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut pixels).unwrap();
+    pixels.truncate(info.buffer_size());
+    Canvas {
+        width: info.width,
+        height: info.height,
+        pixels
+    }
+}
+
+/// Plain polygons go through the polygon rasterizer, everything else through [`compare_lines`].
+fn check(symbol: &str, name: &str) {
+    match symbol.parse::<Symbol>().unwrap() {
+        Symbol::Polygon(polygon) => check_with(&polygon.to_string(), name, options()),
+        symbol => {
+            let (vertices, lines, closed) = symbol.lines(None, None).unwrap();
+            compare_lines(&symbol.to_string(), name, &vertices, &lines, closed)
+        }
+    }
+}
+
+fn check_with(symbol: &str, name: &str, options: RasterOptions) {
+    let polygon = symbol.parse::<Polygon>().unwrap();
+    compare(&polygon.to_string(), name, raster::rasterize(&polygon, &options).unwrap())
+}
+
+fn compare_lines(label: &str, name: &str, vertices: &[Point], lines: &[Vec<Point>], closed: bool) {
+    compare(label, name, raster::rasterize_lines(vertices, lines, closed, &options()).unwrap())
+}
+
+fn check_variant(symbol: &str, variant: Variant, name: &str) {
+    let symbol = symbol.parse::<Symbol>().unwrap();
+    let (vertices, lines, closed) = symbol.lines(Some(variant), None).unwrap();
+    compare_lines(&format!("{} {}", symbol, variant), name, &vertices, &lines, closed)
+}
+
+/// Compares against `tests/golden/<name>.png`; run with `UPDATE_GOLDEN=1` to regenerate the images.
+fn compare(symbol: &str, name: &str, actual: Canvas) {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let golden = root.join("tests/golden").join(format!("{}.png", name));
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::create_dir_all(golden.parent().unwrap()).unwrap();
+        actual.save_png(&golden).unwrap();
+        return
+    }
+    let expected = load_png(&golden);
+    let matches = expected.width == actual.width && expected.height == actual.height
+        && expected.pixels.iter().zip(&actual.pixels).all(|(a, b)| a.abs_diff(*b) <= TOLERANCE);
+    if !matches {
+        let out = root.join("target/golden");
+        fs::create_dir_all(&out).unwrap();
+        actual.save_png(out.join(format!("{}.png", name))).unwrap();
+        panic!("{} differs from {}, actual image written to {}", symbol, golden.display(), out.display());
+    }
+}
+
+#[test]
+fn pentagon() {
+    check("5", "5")
+}
+
+#[test]
+fn pentagram() {
+    check("5/2", "5_2")
+}
+
+#[test]
+fn heptagram() {
+    check("7/3", "7_3")
+}
+
+/// Each triangle in its own colour, as the window draws it.
+#[test]
+fn compound_hexagram() {
+    check_with("6/2", "6_2", RasterOptions {
+        component_colors: palette::component_colors(2).into_iter().map(palette::rgba8).collect(),
+        ..options()
+    })
+}
+
+#[test]
+fn heptagram_density() {
+    check_with("7/3", "7_3_density", RasterOptions {
+        fill: Some(FillMode::Density),
+        ..options()
+    })
+}
+
+#[test]
+fn heptagram_outline() {
+    check_with("7/3", "7_3_outline", RasterOptions {
+        outline: true,
+        ..options()
+    })
+}
+
+#[test]
+fn euclidean_tiling() {
+    check("{6,3}", "6-3")
+}
+
+#[test]
+fn hyperbolic_tiling() {
+    check("{5,4}", "5-4")
+}
+
+#[test]
+fn star_tiling() {
+    check("{5/2,5}", "5_2-5")
+}
+
+#[test]
+fn times_table() {
+    check("60:2", "60-2")
+}
+
+#[test]
+fn chord_expression() {
+    check("60:i*3+1", "60-ix3+1")
+}
+
+#[test]
+fn isotoxal_star() {
+    check_variant("7/2", Variant::Isotoxal(0.7), "7_2_isotoxal")
+}
+
+#[test]
+fn isogonal_polygon() {
+    check_variant("7/2", Variant::Isogonal(0.3), "7_2_isogonal")
+}
+
+#[test]
+fn truncated_heptagram() {
+    check("t{7/3}", "t7_3")
+}
+
+#[test]
+fn composed_operators() {
+    check("dr{8/3}", "dr8_3")
+}
+
+#[test]
+fn hypocycloid_over_pentagram() {
+    let polygon = "5/2".parse::<Polygon>().unwrap();
+    let curve = "hypo(5, 2, 2)".parse::<Symbol>().unwrap();
+    let (vertices, lines, closed) = curve.lines(None, Some(&polygon)).unwrap();
+    compare_lines(&curve.to_string(), "hypo5-2-2_over_5_2", &vertices, &lines, closed)
+}
+
+#[test]
+fn epitrochoid() {
+    check("epi(5, 3, 2)", "epi5-3-2")
+}
+
+#[test]
+fn rose() {
+    check("rose(5/2)", "rose5_2")
+}
+
+#[test]
+fn truncation_doubles_vertices() {
+    let derived = "t{5}".parse::<Derived>().unwrap();
+    assert_eq!(derived.cycles().iter().map(Vec::len).sum::<usize>(), 10);
+}
+
+#[test]
+fn duals_cancel() {
+    assert_eq!("dd{5}".parse::<Symbol>().unwrap(), Symbol::Polygon(Polygon::new(5, 1).unwrap()));
+    assert_eq!("tdd{5}".parse::<Symbol>().unwrap(), "t{5}".parse::<Symbol>().unwrap());
+}
+
+/// Edge midpoints are scaled back out to the unit circle, half an edge round from the vertices.
+#[test]
+fn rectified_vertices() {
+    let derived = "r{7/3}".parse::<Derived>().unwrap();
+    let first = derived.cycles()[0][0];
+    assert!(derived.cycles().concat().iter().all(|p| (p.x.hypot(p.y) - 1.0).abs() < 1e-5));
+    assert!((first.y.atan2(first.x) - 3.0*PI/7.0).abs() < 1e-5);
+}
+
+/// The cusps of `hypo(R, r, r)` visit the vertices of `{R/r}` in the order the polygon does.
+#[test]
+fn hypocycloid_cusps() {
+    let polygon = Polygon::new(5, 2).unwrap();
+    let vertices = polygon.vertices();
+    let points = "hypo(5, 2, 2)".parse::<Curve>().unwrap().points();
+    let nearest = polygon.cycles()[0].iter().map(|&i| {
+        let distance = |p: &Point| (p.x - vertices[i].x).hypot(p.y - vertices[i].y);
+        let (j, p) = points.iter().enumerate().min_by(|(_, a), (_, b)| distance(a).total_cmp(&distance(b))).unwrap();
+        assert!(distance(p) < 1e-3, "no cusp at vertex {}", i);
+        j
+    }).collect::<Vec<usize>>();
+    assert!(nearest.windows(2).all(|w| w[0] < w[1]), "cusps out of order: {:?}", nearest);
+}
+
+#[test]
+fn isotoxal_depth() {
+    let polygon = Polygon::new(5, 2).unwrap();
+    let dent = |variant: Variant| {
+        let p = variant.cycles(&polygon)[0][1];
+        p.x.hypot(p.y)
+    };
+    let side = (PI/5.0).cos();
+    for depth in [0.0, 0.25, 0.5, 1.0] {
+        assert!((dent(Variant::Isotoxal(depth)) - (1.0 - depth)*side).abs() < 1e-6);
+    }
+    // The star drawn around {5/2} dents to its inner corners.
+    assert!((dent(Variant::isotoxal(&polygon)) - (2.0*PI/5.0).cos()/(PI/5.0).cos()).abs() < 1e-5);
+}